@@ -1,4 +1,4 @@
-use std::io::{prelude::*, stdin};
+use std::{io::{prelude::*, stdin}, iter};
 use sscanf::sscanf;
 
 pub struct Elf {
@@ -10,22 +10,20 @@ impl Elf {
         Self { calories: vec! [] }
     }
 
-    pub fn parse<R: BufRead>(reader: R) -> Vec<Self> {
-        let mut elves = vec! [
-            Elf::empty()
-        ];
-
-        for line in reader.lines() {
-            let line = line.unwrap();
-
-            if line.is_empty() {
-                elves.push(Elf::empty());
-            } else if let Ok(item) = sscanf!(line, "{}", usize) {
-                elves.last_mut().unwrap().calories.push(item);
-            }
+    fn from_lines(lines: &[String]) -> Self {
+        Self {
+            calories: lines.iter()
+                .filter_map(|line| sscanf!(line, "{}", usize).ok())
+                .collect()
         }
+    }
+
+    pub fn parse<R: BufRead>(reader: R) -> Vec<Self> {
+        Self::iter_elves(reader).collect()
+    }
 
-        elves
+    pub fn iter_elves<R: BufRead>(reader: R) -> impl Iterator<Item=Self> {
+        groups(reader).map(|lines| Self::from_lines(&lines))
     }
 
     pub fn total(&self) -> usize {
@@ -33,6 +31,17 @@ impl Elf {
     }
 }
 
+/// Yields every block of lines separated by blank lines.
+pub fn groups<R: BufRead>(reader: R) -> impl Iterator<Item=Vec<String>> {
+    let mut lines = reader.lines().map(|line| line.unwrap()).peekable();
+
+    iter::from_fn(move || {
+        lines.peek()?;
+
+        Some(lines.by_ref().take_while(|line| !line.is_empty()).collect())
+    })
+}
+
 fn top_3(elves: &[usize]) -> Vec<usize> {
     let n = elves.len();
     let mut ordered_elves = elves.to_vec();
@@ -85,4 +94,43 @@ mod tests {
         assert_eq!(elves[4].calories, vec! [10000]);
         assert_eq!(elves[4].total(), 10000);
     }
+
+    #[test]
+    fn _01_groups() {
+        let example = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+        let blocks = groups(Cursor::new(example)).collect::<Vec<_>>();
+
+        assert_eq!(blocks.len(), 5);
+        assert_eq!(blocks[0], vec! ["1000", "2000", "3000"]);
+        assert_eq!(blocks[4], vec! ["10000"]);
+        assert_eq!(groups(Cursor::new("1\n\n\n2\n")).collect::<Vec<_>>(), vec! [vec! ["1"], vec! [], vec! ["2"]]);
+        assert_eq!(groups(Cursor::new("")).count(), 0);
+    }
+
+    #[test]
+    fn _01_iter_elves() {
+        let example = r#"1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000"#;
+
+        let elves = Elf::parse(Cursor::new(&example));
+        let lazy_elves = Elf::iter_elves(Cursor::new(&example)).collect::<Vec<_>>();
+
+        assert_eq!(lazy_elves.len(), 5);
+        for (lazy_elf, elf) in lazy_elves.iter().zip(elves.iter()) {
+            assert_eq!(lazy_elf.calories, elf.calories);
+        }
+        assert_eq!(Elf::iter_elves(Cursor::new(&example)).map(|elf| elf.total()).max(), Some(24000));
+    }
 }
@@ -1,7 +1,9 @@
 use std::io::{prelude::*, stdin};
 use sscanf::sscanf;
 
-struct Round {
+const NUM_SHAPES: i64 = 3;
+
+pub struct Round {
     opponent: char,
     to_play: char
 }
@@ -27,49 +29,95 @@ impl Round {
         rounds
     }
 
-    pub fn score(&self) -> usize {
-        match (self.opponent, self.to_play) {
-            ('A', 'X') => 1 + 3,
-            ('A', 'Y') => 2 + 6,
-            ('A', 'Z') => 3 + 0,
+    /// Returns the index of `ch` relative to `first`, i.e. `0` for rock, `1`
+    /// for paper, and `2` for scissors.
+    fn shape_index(ch: char, first: char) -> Option<i64> {
+        let index = ch as i64 - first as i64;
+
+        (0..NUM_SHAPES).contains(&index).then_some(index)
+    }
 
-            ('B', 'X') => 1 + 0,
-            ('B', 'Y') => 2 + 3,
-            ('B', 'Z') => 3 + 6,
+    /// Returns `0` for a loss, `1` for a draw, and `2` for a win.
+    fn outcome(mine: i64, theirs: i64) -> i64 {
+        (mine - theirs + 1).rem_euclid(NUM_SHAPES)
+    }
 
-            ('C', 'X') => 1 + 6,
-            ('C', 'Y') => 2 + 0,
-            ('C', 'Z') => 3 + 3,
+    /// Returns the shape to play against `theirs` to get `outcome`.
+    fn shape_for_outcome(theirs: i64, outcome: i64) -> i64 {
+        (theirs + outcome - 1).rem_euclid(NUM_SHAPES)
+    }
 
+    /// Returns the shape the opponent played if playing `mine` gave
+    /// `outcome`, the inverse of `shape_for_outcome`.
+    pub fn opponent_move(mine: i64, outcome: i64) -> i64 {
+        (mine - outcome + 1).rem_euclid(NUM_SHAPES)
+    }
+
+    fn points(mine: i64, outcome: i64) -> usize {
+        (mine as usize + 1) + 3 * outcome as usize
+    }
+
+    pub fn score(&self) -> usize {
+        match (Self::shape_index(self.opponent, 'A'), Self::shape_index(self.to_play, 'X')) {
+            (Some(theirs), Some(mine)) => Self::points(mine, Self::outcome(mine, theirs)),
             _ => 0
         }
     }
 
     pub fn score2(&self) -> usize {
-        match (self.opponent, self.to_play) {
-            ('A', 'X') => 3 + 0,
-            ('A', 'Y') => 1 + 3,
-            ('A', 'Z') => 2 + 6,
+        match (Self::shape_index(self.opponent, 'A'), Self::shape_index(self.to_play, 'X')) {
+            (Some(theirs), Some(outcome)) => Self::points(Self::shape_for_outcome(theirs, outcome), outcome),
+            _ => 0
+        }
+    }
 
-            ('B', 'X') => 1 + 0,
-            ('B', 'Y') => 2 + 3,
-            ('B', 'Z') => 3 + 6,
+    pub fn score_with(&self, strategy: Strategy) -> usize {
+        match strategy {
+            Strategy::PartOne => self.score(),
+            Strategy::PartTwo => self.score2()
+        }
+    }
+}
 
-            ('C', 'X') => 2 + 0,
-            ('C', 'Y') => 3 + 3,
-            ('C', 'Z') => 1 + 6,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    PartOne,
+    PartTwo
+}
 
-            _ => 0
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    score: usize
+}
+
+fn summary(rounds: &[Round], strategy: Strategy) -> Tally {
+    let mut tally = Tally::default();
+
+    for round in rounds {
+        let score = round.score_with(strategy);
+
+        match score {
+            1..=3 => tally.losses += 1,
+            4..=6 => tally.draws += 1,
+            7..=9 => tally.wins += 1,
+            _ => {}
         }
+
+        tally.score += score;
     }
+
+    tally
 }
 
 fn main() {
     let stdin = stdin().lock();
     let rounds = Round::parse_all(stdin);
 
-    println!("{}", rounds.iter().map(|round| round.score()).sum::<usize>());
-    println!("{}", rounds.iter().map(|round| round.score2()).sum::<usize>());
+    println!("{}", summary(&rounds, Strategy::PartOne).score);
+    println!("{}", summary(&rounds, Strategy::PartTwo).score);
 }
 
 #[cfg(test)]
@@ -96,4 +144,36 @@ C Z"#;
 
         assert_eq!(rounds.iter().map(|round| round.score2()).sum::<usize>(), 12);
     }
+
+    #[test]
+    fn _01_score() {
+        let rounds = Round::parse_all(Cursor::new("A X\nA Y\nA Z\nB X\nB Y\nB Z\nC X\nC Y\nC Z"));
+
+        assert_eq!(rounds.iter().map(|round| round.score()).collect::<Vec<_>>(), vec! [4, 8, 3, 1, 5, 9, 7, 2, 6]);
+        assert_eq!(rounds.iter().map(|round| round.score2()).collect::<Vec<_>>(), vec! [3, 4, 8, 1, 5, 9, 2, 6, 7]);
+        assert_eq!(Round::parse("D X").map(|round| round.score()), Some(0));
+    }
+
+    #[test]
+    fn _02_opponent_move() {
+        for theirs in 0..NUM_SHAPES {
+            for outcome in 0..3 {
+                let mine = Round::shape_for_outcome(theirs, outcome);
+
+                assert_eq!(Round::outcome(mine, theirs), outcome);
+                assert_eq!(Round::opponent_move(mine, outcome), theirs);
+            }
+        }
+    }
+
+    #[test]
+    fn _01_summary() {
+        let example = r#"A Y
+B X
+C Z"#;
+        let rounds = Round::parse_all(Cursor::new(&example));
+
+        assert_eq!(summary(&rounds, Strategy::PartOne), Tally { wins: 1, draws: 1, losses: 1, score: 15 });
+        assert_eq!(summary(&rounds, Strategy::PartTwo), Tally { wins: 1, draws: 1, losses: 1, score: 12 });
+    }
 }
@@ -1,5 +1,13 @@
 use std::{io::{prelude::*, stdin}};
 
+pub fn priority(item_type: char) -> usize {
+    match item_type {
+        'a'..='z' => 1 + (item_type as usize - 'a' as usize),
+        'A'..='Z' => 27 + (item_type as usize - 'A' as usize),
+        _ => panic!()
+    }
+}
+
 pub struct Item {
     item_type: char
 }
@@ -10,11 +18,7 @@ impl Item {
     }
 
     fn score(&self) -> usize {
-        match self.item_type {
-            'a'..='z' => 1 + (self.item_type as usize - 'a' as usize),
-            'A'..='Z' => 27 + (self.item_type as usize - 'A' as usize),
-            _ => panic!()
-        }
+        priority(self.item_type)
     }
 }
 
@@ -93,6 +97,21 @@ impl ElfGroup {
     }
 }
 
+/// Sums the badge priorities of every group of three elves, reading one group
+/// at a time from `reader`. A trailing incomplete group is ignored.
+pub fn group_priority_sum<R: BufRead>(reader: R) -> usize {
+    let mut lines = reader.lines().map(|line| line.unwrap());
+    let mut sum = 0;
+
+    while let (Some(first), Some(second), Some(third)) = (lines.next(), lines.next(), lines.next()) {
+        let group = ElfGroup::new(vec! [Rucksack::new(&first), Rucksack::new(&second), Rucksack::new(&third)]);
+
+        sum += group.common_items().into_iter().map(priority).sum::<usize>();
+    }
+
+    sum
+}
+
 fn main() {
     let stdin = stdin().lock();
     let rucksacks = Rucksack::parse_all(stdin);
@@ -107,6 +126,14 @@ mod tests {
     use std::io::Cursor;
     use super::*;
 
+    #[test]
+    fn _01_priority() {
+        assert_eq!(priority('a'), 1);
+        assert_eq!(priority('z'), 26);
+        assert_eq!(priority('A'), 27);
+        assert_eq!(priority('Z'), 52);
+    }
+
     #[test]
     fn _01_left_compartment() {
         let rucksack = Rucksack::new("vJrwpWtwJgWrhcsFMMfFFhFp");
@@ -157,4 +184,17 @@ CrZsJsPPZsGzwwsLwLmpwMDw"#;
         assert_eq!(groups[1].common_items(), vec! ['Z']);
         assert_eq!(groups.iter().flat_map(|group| group.common_items()).map(|item_type| Item::new(item_type).score()).sum::<usize>(), 70);
     }
+
+    #[test]
+    fn _02_group_priority_sum() {
+        const EXAMPLE: &str = r#"vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw"#;
+
+        assert_eq!(group_priority_sum(Cursor::new(EXAMPLE)), 70);
+        assert_eq!(group_priority_sum(Cursor::new(EXAMPLE.lines().take(5).collect::<Vec<_>>().join("\n"))), 18);
+    }
 }
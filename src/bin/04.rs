@@ -7,11 +7,17 @@ struct SectionAssignment {
 }
 
 impl SectionAssignment {
+    /// Parses either `lower-upper` (inclusive) or `lower..upper` (exclusive
+    /// upper bound), always storing the inclusive bounds.
     fn parse(line: &str) -> Option<Self> {
         if let Ok((lower, upper)) = sscanf!(line, "{}-{}", usize, usize) {
             assert!(lower <= upper);
 
             Some(Self { lower, upper })
+        } else if let Ok((lower, upper)) = sscanf!(line, "{}..{}", usize, usize) {
+            assert!(lower < upper);
+
+            Some(Self { lower, upper: upper - 1 })
         } else {
             None
         }
@@ -27,7 +33,7 @@ impl SectionAssignment {
     }
 }
 
-struct SectionAssignmentPair {
+pub struct SectionAssignmentPair {
     assignments: Vec<SectionAssignment>
 }
 
@@ -66,6 +72,51 @@ impl SectionAssignmentPair {
     fn has_overlapping_assignments(&self) -> bool {
         self.any_pair_matches(|a, b| a.overlap(b))
     }
+
+    pub fn merged_ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges = self.assignments.iter()
+            .map(|a| (a.lower, a.upper))
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = vec! [];
+
+        for (lower, upper) in ranges {
+            if let Some(last) = merged.last_mut() {
+                if lower <= last.1 + 1 {
+                    last.1 = last.1.max(upper);
+                    continue;
+                }
+            }
+
+            merged.push((lower, upper));
+        }
+
+        merged
+    }
+
+    /// Returns the number of sections assigned to exactly one elf.
+    pub fn singly_covered(&self) -> usize {
+        let mut events = self.assignments.iter()
+            .flat_map(|a| [(a.lower, 1), (a.upper + 1, -1)])
+            .collect::<Vec<(usize, isize)>>();
+        events.sort_unstable();
+
+        let mut count = 0;
+        let mut coverage = 0;
+        let mut prev = 0;
+
+        for (at, delta) in events {
+            if coverage == 1 {
+                count += at - prev;
+            }
+
+            coverage += delta;
+            prev = at;
+        }
+
+        count
+    }
 }
 
 fn main() {
@@ -103,4 +154,38 @@ mod tests {
         assert_eq!(assignment_pairs.len(), 6);
         assert_eq!(assignment_pairs.iter().filter(|p| p.has_overlapping_assignments()).count(), 4);
     }
+
+    #[test]
+    fn _03_exclusive_ranges() {
+        let inclusive = SectionAssignment::parse("2-4").unwrap();
+        let exclusive = SectionAssignment::parse("2..5").unwrap();
+
+        assert_eq!((exclusive.lower, exclusive.upper), (inclusive.lower, inclusive.upper));
+        assert_eq!(SectionAssignmentPair::parse("2..5,5..9").merged_ranges(), vec! [(2, 8)]);
+        assert!(SectionAssignmentPair::parse("2..9,3-7").has_redundant_assignment());
+        assert!(!SectionAssignmentPair::parse("2..5,5-7").has_overlapping_assignments());
+        assert!(SectionAssignmentPair::parse("2..6,5-7").has_overlapping_assignments());
+    }
+
+    #[test]
+    fn _03_merged_ranges_disjoint() {
+        assert_eq!(SectionAssignmentPair::parse("2-4,6-8").merged_ranges(), vec! [(2, 4), (6, 8)]);
+        assert_eq!(SectionAssignmentPair::parse("6-8,2-4").merged_ranges(), vec! [(2, 4), (6, 8)]);
+    }
+
+    #[test]
+    fn _03_merged_ranges_touching() {
+        assert_eq!(SectionAssignmentPair::parse("2-4,4-8").merged_ranges(), vec! [(2, 8)]);
+        assert_eq!(SectionAssignmentPair::parse("2-3,4-5").merged_ranges(), vec! [(2, 5)]);
+        assert_eq!(SectionAssignmentPair::parse("2-8,3-7").merged_ranges(), vec! [(2, 8)]);
+    }
+
+    #[test]
+    fn _03_singly_covered() {
+        assert_eq!(SectionAssignmentPair::parse("2-4,6-8").singly_covered(), 6);
+        assert_eq!(SectionAssignmentPair::parse("2-8,3-7").singly_covered(), 2);
+        assert_eq!(SectionAssignmentPair::parse("2-6,4-8").singly_covered(), 4);
+        assert_eq!(SectionAssignmentPair::parse("5-7,7-9").singly_covered(), 4);
+        assert_eq!(SectionAssignmentPair::parse("6-6,6-6").singly_covered(), 0);
+    }
 }
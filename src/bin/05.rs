@@ -42,30 +42,41 @@ enum CrateParseResult {
 }
 
 impl Crates {
+    fn empty() -> Self {
+        Self { crates: vec! [] }
+    }
+
+    #[cfg(test)]
     fn parse<R: BufRead>(reader: &mut R) -> Self {
-        let mut crates = vec! [];
+        let mut crates = Self::empty();
 
         for line in reader.lines().filter_map(|line| line.ok()) {
-            match Self::parse_line(line) {
-                CrateParseResult::None => break,
-                CrateParseResult::Label(_) => {
-                    // pass
-                },
-                CrateParseResult::Crates(crates_row) => {
-                    for (i, &bottom) in crates_row.iter().enumerate() {
-                        if crates.len() <= i {
-                            crates.resize(i + 1, Crate::empty());
-                        }
-
-                        if bottom != '\0' {
-                            crates[i].push_front(bottom);
-                        }
+            if !crates.push_line(line) {
+                break
+            }
+        }
+
+        crates
+    }
+
+    /// Adds one line of the stack drawing, returns `false` once the blank
+    /// line separating the stacks from the rearrangements is reached.
+    fn push_line(&mut self, line: String) -> bool {
+        match Self::parse_line(line) {
+            CrateParseResult::None => false,
+            CrateParseResult::Label(_) => true,
+            CrateParseResult::Crates(crates_row) => {
+                for (i, &bottom) in crates_row.iter().enumerate() {
+                    self.ensure_stack(i);
+
+                    if bottom != '\0' {
+                        self.crates[i].push_front(bottom);
                     }
                 }
+
+                true
             }
         }
-
-        Self { crates }
     }
 
     fn parse_line(line: String) -> CrateParseResult {
@@ -114,13 +125,23 @@ impl Crates {
         parts
     }
 
+    /// Grows the stacks with empty ones so that `index` is a valid stack.
+    fn ensure_stack(&mut self, index: usize) {
+        if self.crates.len() <= index {
+            self.crates.resize(index + 1, Crate::empty());
+        }
+    }
+
     fn move_to(&mut self, from: usize, to: usize) {
+        self.ensure_stack(from.max(to));
+
         if let Some(ch) = self.crates[from].pop() {
             self.crates[to].push_back(ch);
         }
     }
 
     fn move_multiple_to(&mut self, amount: usize, from: usize, to: usize) {
+        self.ensure_stack(from.max(to));
         let mut temp = (0..amount).filter_map(|_| self.crates[from].pop()).collect::<Vec<_>>();
 
         while let Some(c) = temp.pop() {
@@ -128,11 +149,41 @@ impl Crates {
         }
     }
 
+    /// Apply every rearrangement in `ops` using the given crane, and return
+    /// the crates on top of each stack.
+    fn run_all(&mut self, ops: &[Rearrangement], model: MoverModel) -> String {
+        for op in ops {
+            match model {
+                MoverModel::OneAtATime => {
+                    for _ in 0..op.amount {
+                        self.move_to(op.from(), op.to());
+                    }
+                },
+                MoverModel::PreserveOrder => self.move_multiple_to(op.amount, op.from(), op.to()),
+            }
+        }
+
+        self.top().iter().collect()
+    }
+
     fn top(&self) -> Vec<char> {
-        self.crates.iter().filter_map(|c| c.peek()).collect()
+        self.tops().into_iter().flatten().collect()
+    }
+
+    /// Returns the top crate of every stack, with `None` for empty stacks.
+    fn tops(&self) -> Vec<Option<char>> {
+        self.crates.iter().map(|c| c.peek()).collect()
     }
 }
 
+/// How the crane moves several crates at once, the `CrateMover 9000` moves them
+/// one at a time while the `CrateMover 9001` keeps their order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MoverModel {
+    OneAtATime,
+    PreserveOrder,
+}
+
 #[derive(PartialEq, Debug)]
 struct Rearrangement {
     amount: usize,
@@ -141,16 +192,19 @@ struct Rearrangement {
 }
 
 impl Rearrangement {
+    fn parse(line: &str) -> Option<Self> {
+        if let Ok((amount, from, to)) = sscanf!(line, "move {} from {} to {}", usize, usize, usize) {
+            Some(Rearrangement { amount, from: from, to })
+        } else {
+            None
+        }
+    }
+
+    #[cfg(test)]
     fn parse_all<R: BufRead>(reader: &mut R) -> Vec<Self> {
         reader.lines()
             .filter_map(|line| line.ok())
-            .filter_map(|line| {
-                if let Ok((amount, from, to)) = sscanf!(line, "move {} from {} to {}", usize, usize, usize) {
-                    Some(Rearrangement { amount, from: from, to })
-                } else {
-                    None
-                }
-            })
+            .filter_map(|line| Self::parse(&line))
             .collect()
     }
 
@@ -163,22 +217,29 @@ impl Rearrangement {
     }
 }
 
-fn main() {
-    let mut stdin = stdin().lock();
-    let mut crates = Crates::parse(&mut stdin);
-    let mut crates2 = crates.clone();
-    let rearrangements = Rearrangement::parse_all(&mut stdin);
+fn parse_input<R: BufRead>(reader: &mut R) -> (Crates, Vec<Rearrangement>) {
+    let mut crates = Crates::empty();
+    let mut rearrangements = vec! [];
+    let mut in_rearrangements = false;
 
-    for op in &rearrangements {
-        for _ in 0..op.amount {
-            crates.move_to(op.from(), op.to());
+    for line in reader.lines().map_while(Result::ok) {
+        if in_rearrangements {
+            rearrangements.extend(Rearrangement::parse(&line));
+        } else {
+            in_rearrangements = !crates.push_line(line);
         }
-
-        crates2.move_multiple_to(op.amount, op.from(), op.to());
     }
 
-    println!("{}", crates.top().iter().collect::<String>());
-    println!("{}", crates2.top().iter().collect::<String>());
+    (crates, rearrangements)
+}
+
+fn main() {
+    let mut stdin = stdin().lock();
+    let (mut crates, rearrangements) = parse_input(&mut stdin);
+    let mut crates2 = crates.clone();
+
+    println!("{}", crates.run_all(&rearrangements, MoverModel::OneAtATime));
+    println!("{}", crates2.run_all(&rearrangements, MoverModel::PreserveOrder));
 }
 
 #[cfg(test)]
@@ -231,4 +292,52 @@ move 1 from 1 to 2"#;
 
         assert_eq!(crates.top(), vec! ['M', 'C', 'D']);
     }
+
+    #[test]
+    fn _03_run_all() {
+        let (crates, rearrangements) = parse_input(&mut Cursor::new(EXAMPLE));
+
+        assert_eq!(crates.clone().run_all(&rearrangements, MoverModel::OneAtATime), "CMZ");
+        assert_eq!(crates.clone().run_all(&rearrangements, MoverModel::PreserveOrder), "MCD");
+        assert_eq!(crates.clone().run_all(&[], MoverModel::OneAtATime), "NDP");
+    }
+
+    #[test]
+    fn _03_new_stack() {
+        let mut crates = Crates::parse(&mut Cursor::new(EXAMPLE));
+
+        crates.move_to(1, 3);
+        assert_eq!(crates.crates.len(), 4);
+        assert_eq!(crates.crates[3].stack, vec! ['D']);
+
+        crates.move_multiple_to(2, 0, 5);
+        assert_eq!(crates.crates.len(), 6);
+        assert!(crates.crates[4].stack.is_empty());
+        assert_eq!(crates.crates[5].stack, vec! ['Z', 'N']);
+        assert_eq!(crates.top(), vec! ['C', 'P', 'D', 'N']);
+    }
+
+    #[test]
+    fn _03_tops() {
+        let mut crates = Crates::parse(&mut Cursor::new("[A] [B] [C]\n 1   2   3"));
+
+        assert_eq!(crates.crates[1].pop(), Some('B'));
+        assert_eq!(crates.tops(), vec! [Some('A'), None, Some('C')]);
+        assert_eq!(crates.top(), vec! ['A', 'C']);
+    }
+
+    #[test]
+    fn _03_parse_input() {
+        let mut reader = Cursor::new(EXAMPLE);
+        let crates = Crates::parse(&mut reader);
+        let rearrangements = Rearrangement::parse_all(&mut reader);
+        let (single_crates, single_rearrangements) = parse_input(&mut Cursor::new(EXAMPLE));
+
+        assert_eq!(single_crates.crates.len(), crates.crates.len());
+        for (single, expected) in single_crates.crates.iter().zip(crates.crates.iter()) {
+            assert_eq!(single.stack, expected.stack);
+        }
+        assert_eq!(single_rearrangements, rearrangements);
+        assert_eq!(single_rearrangements.len(), 4);
+    }
 }
@@ -1,6 +1,6 @@
-use std::io::{prelude::*, stdin};
+use std::{io::{prelude::*, stdin, BufReader}, collections::{HashMap, VecDeque}};
 
-struct DataStreamBuffer {
+pub struct DataStreamBuffer {
     characters: Vec<char>
 }
 
@@ -24,21 +24,107 @@ impl DataStreamBuffer {
         received.len() == size
     }
 
+    #[cfg(test)]
     fn is_start_of_packet(&self, index: usize) -> bool {
         self.is_distinct_sequence(index, 4)
     }
 
+    #[cfg(test)]
     fn is_start_of_message(&self, index: usize) -> bool {
         self.is_distinct_sequence(index, 14)
     }
+
+    /// Returns the end of the first distinct window of `size` characters that
+    /// lies entirely after position `after`.
+    pub fn first_distinct_after(&self, size: usize, after: usize) -> Option<usize> {
+        (after + size..=self.len()).find(|&i| self.is_distinct_sequence(i, size))
+    }
+
+    /// Yields `(end, run_length)` for every character, where `run_length` is
+    /// the length of the longest run of distinct characters ending at `end`.
+    fn distinct_runs(&self) -> impl Iterator<Item=(usize, usize)> + '_ {
+        let mut last_seen = HashMap::new();
+        let mut run_start = 0;
+
+        self.characters.iter().enumerate().map(move |(i, &ch)| {
+            if let Some(prev) = last_seen.insert(ch, i) {
+                run_start = run_start.max(prev + 1);
+            }
+
+            (i + 1, i + 1 - run_start)
+        })
+    }
+
+    /// Returns the start-of-packet and start-of-message positions, found by
+    /// tracking the longest run of distinct characters ending at each index.
+    pub fn markers(&self) -> (Option<usize>, Option<usize>) {
+        let mut packet = None;
+        let mut message = None;
+
+        for (end, run_length) in self.distinct_runs() {
+            if packet.is_none() && run_length >= 4 {
+                packet = Some(end);
+            }
+
+            if run_length >= 14 {
+                message = Some(end);
+                break;
+            }
+        }
+
+        (packet, message)
+    }
+
+    /// Returns the length of the longest window of distinct characters found
+    /// anywhere in the stream.
+    pub fn max_distinct_run(&self) -> usize {
+        self.distinct_runs().map(|(_, run_length)| run_length).max().unwrap_or(0)
+    }
+}
+
+pub fn scan_reader<R: Read>(reader: R, size: usize) -> Option<usize> {
+    let mut counts = [0usize; 256];
+    let mut window = VecDeque::with_capacity(size + 1);
+    let mut distinct = 0;
+
+    for (i, byte) in BufReader::new(reader).bytes().enumerate() {
+        let byte = byte.ok()?;
+
+        if byte == b'\n' {
+            break;
+        }
+
+        window.push_back(byte);
+        counts[byte as usize] += 1;
+        if counts[byte as usize] == 1 {
+            distinct += 1;
+        }
+
+        if window.len() > size {
+            let removed = window.pop_front().unwrap();
+
+            counts[removed as usize] -= 1;
+            if counts[removed as usize] == 0 {
+                distinct -= 1;
+            }
+        }
+
+        if distinct == size {
+            return Some(i + 1);
+        }
+    }
+
+    None
 }
 
 fn main() {
     if let Some(Ok(line)) =stdin().lock().lines().next() {
         let buf = DataStreamBuffer::new(&line);
 
-        println!("{}", (0..buf.len()).filter(|&i| buf.is_start_of_packet(i)).next().unwrap());
-        println!("{}", (0..buf.len()).filter(|&i| buf.is_start_of_message(i)).next().unwrap());
+        let (packet, message) = buf.markers();
+
+        println!("{}", packet.unwrap());
+        println!("{}", message.unwrap());
     }
 }
 
@@ -125,4 +211,52 @@ mod tests {
 
         assert_eq!((0..buf.len()).filter(|&i| buf.is_start_of_message(i)).next(), Some(26));
     }
+
+    #[test]
+    fn _03_markers() {
+        assert_eq!(DataStreamBuffer::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb").markers(), (Some(7), Some(19)));
+        assert_eq!(DataStreamBuffer::new("bvwbjplbgvbhsrlpgdmjqwftvncz").markers(), (Some(5), Some(23)));
+        assert_eq!(DataStreamBuffer::new("nppdvjthqldpwncqszvftbrmjlhg").markers(), (Some(6), Some(23)));
+        assert_eq!(DataStreamBuffer::new("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").markers(), (Some(10), Some(29)));
+        assert_eq!(DataStreamBuffer::new("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").markers(), (Some(11), Some(26)));
+        assert_eq!(DataStreamBuffer::new("aabb").markers(), (None, None));
+    }
+
+    #[test]
+    fn _03_first_distinct_after() {
+        let buf = DataStreamBuffer::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb");
+        let packet = buf.first_distinct_after(4, 0);
+
+        assert_eq!(packet, Some(7));
+        assert_eq!(buf.first_distinct_after(4, packet.unwrap()), Some(11));
+        assert_eq!(buf.first_distinct_after(14, 0), Some(19));
+        assert_eq!(buf.first_distinct_after(4, buf.len()), None);
+        assert_eq!(DataStreamBuffer::new("aabb").first_distinct_after(2, 1), Some(3));
+    }
+
+    #[test]
+    fn _03_max_distinct_run() {
+        assert_eq!(DataStreamBuffer::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb").max_distinct_run(), 18);
+        assert_eq!(DataStreamBuffer::new("aabb").max_distinct_run(), 2);
+        assert_eq!(DataStreamBuffer::new("abcabcbb").max_distinct_run(), 3);
+        assert_eq!(DataStreamBuffer::new("").max_distinct_run(), 0);
+    }
+
+    #[test]
+    fn _03_scan_reader() {
+        const EXAMPLES: [&str; 5] = [
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+        ];
+
+        for example in EXAMPLES {
+            let buf = DataStreamBuffer::new(example);
+
+            assert_eq!(scan_reader(example.as_bytes(), 4), (0..buf.len()).find(|&i| buf.is_start_of_packet(i)));
+            assert_eq!(scan_reader(example.as_bytes(), 14), (0..buf.len()).find(|&i| buf.is_start_of_message(i)));
+        }
+    }
 }
@@ -46,8 +46,13 @@ impl FsDirectory {
         }
     }
 
+    /// Adds `entry` under `name`, keeping the children of a directory that
+    /// has already been listed.
     fn insert(&mut self, name: String, entry: FsEntry) {
-        self.entries.insert(name, entry);
+        match (self.entries.get(&name), &entry) {
+            (Some(FsEntry::Directory(_)), FsEntry::Directory(_)) => {},
+            _ => { self.entries.insert(name, entry); }
+        }
     }
 
     fn size(&self) -> usize {
@@ -63,7 +68,14 @@ impl FsDirectory {
     }
 }
 
-struct FsConsumer {
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FsStats {
+    pub files: usize,
+    pub directories: usize,
+    pub max_depth: usize,
+}
+
+pub struct FsConsumer {
     root: FsDirectory,
     current_path: Vec<String>
 }
@@ -104,13 +116,31 @@ impl FsConsumer {
         self.root().traverse(&f, initial_value)
     }
 
-    fn consume(&mut self, line: String) {
-        if let Ok(_) = sscanf!(line, "$ cd /") {
+    /// Returns the number of files and directories (including the root), and
+    /// the number of path components to the most deeply nested entry.
+    pub fn stats(&self) -> FsStats {
+        let root_stats = FsStats { directories: 1, ..FsStats::default() };
+
+        self.traverse(collect_stats, (root_stats, 0)).0
+    }
+
+    fn change_directory(&mut self, path: &str) {
+        if path.starts_with('/') {
             self.current_path.clear();
-        } else if let Ok(_) = sscanf!(line, "$ cd ..") {
-            self.current_path.pop();
-        } else if let Ok(new_directory) = sscanf!(line, "$ cd {}", String) {
-            self.current_path.push(new_directory);
+        }
+
+        for component in path.split('/').filter(|component| !component.is_empty()) {
+            if component == ".." {
+                self.current_path.pop();
+            } else {
+                self.current_path.push(component.to_string());
+            }
+        }
+    }
+
+    fn consume(&mut self, line: String) {
+        if let Ok(path) = sscanf!(line, "$ cd {}", String) {
+            self.change_directory(&path);
         } else if let Ok(_) = sscanf!(line, "$ ls") {
             // pass
         } else if let Ok(directory_name) = sscanf!(line, "dir {}", String) {
@@ -133,6 +163,18 @@ fn sum_of_at_most_100000(mut acc: usize, entry: &FsEntry) -> usize {
     entry.traverse(&sum_of_at_most_100000, acc)
 }
 
+fn collect_stats((mut stats, depth): (FsStats, usize), entry: &FsEntry) -> (FsStats, usize) {
+    stats.max_depth = stats.max_depth.max(depth + 1);
+
+    if entry.is_dir() {
+        stats.directories += 1;
+        (entry.traverse(&collect_stats, (stats, depth + 1)).0, depth)
+    } else {
+        stats.files += 1;
+        (stats, depth)
+    }
+}
+
 fn smallest_bigger_than(limit: usize) -> impl Fn(usize, &FsEntry) -> usize {
     move |acc, entry| {
         let entry_size = entry.size();
@@ -193,6 +235,40 @@ $ ls
         assert_eq!(consumer.traverse(sum_of_at_most_100000, 0), 95437);
     }
 
+    #[test]
+    fn _01_stats() {
+        let consumer = FsConsumer::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(consumer.stats(), FsStats { files: 10, directories: 4, max_depth: 3 });
+        assert_eq!(FsConsumer::parse_all(Cursor::new("$ cd /")).stats(), FsStats { files: 0, directories: 1, max_depth: 0 });
+    }
+
+    #[test]
+    fn _01_cd_path() {
+        let transcript = format!("{}\n$ cd /a/e\n$ ls\n100 z\n$ cd /\n$ cd d/../a\n$ ls\n200 y", EXAMPLE);
+        let mut consumer = FsConsumer::parse_all(Cursor::new(transcript));
+
+        assert_eq!(consumer.current_path, vec! ["a"]);
+        assert_eq!(consumer.root().size(), 48381465);
+
+        consumer.change_directory("/a/e");
+        assert_eq!(consumer.get_current_directory().size(), 684);
+        assert!(consumer.get_current_directory().entries.contains_key("z"));
+
+        consumer.change_directory("../..");
+        assert!(consumer.current_path.is_empty());
+    }
+
+    #[test]
+    fn _01_repeated_ls() {
+        let transcript = format!("{}\n$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d", EXAMPLE);
+        let consumer = FsConsumer::parse_all(Cursor::new(transcript));
+
+        assert_eq!(consumer.root().size(), 48381165);
+        assert_eq!(consumer.stats(), FsStats { files: 10, directories: 4, max_depth: 3 });
+        assert_eq!(consumer.traverse(sum_of_at_most_100000, 0), 95437);
+    }
+
     #[test]
     fn _02_example() {
         let consumer = FsConsumer::parse_all(Cursor::new(EXAMPLE));
@@ -1,16 +1,50 @@
-use std::io::{prelude::*, stdin};
+use std::{io::{prelude::*, stdin}, ops::BitOr};
 use ndarray::*;
 
-struct Forest {
+/// The edges of the forest that a tree is visible from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Directions(u8);
+
+impl Directions {
+    pub const NONE: Self = Self(0);
+    pub const TOP: Self = Self(1);
+    pub const BOTTOM: Self = Self(2);
+    pub const LEFT: Self = Self(4);
+    pub const RIGHT: Self = Self(8);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Directions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+pub struct Forest {
     /// column-major order
     trees: Array2<u32>
 }
 
 impl Forest {
     fn parse_all<R: BufRead>(reader: R) -> Self {
+        Self::parse_rows(reader, |line| Array::from_iter(line.chars().filter_map(|ch| ch.to_digit(10))))
+    }
+
+    /// Parse a forest where every row lists its tree heights separated by
+    /// whitespace, so that heights are not limited to a single digit.
+    pub fn parse_spaced<R: BufRead>(reader: R) -> Self {
+        Self::parse_rows(reader, |line| Array::from_iter(line.split_whitespace().filter_map(|token| token.parse::<u32>().ok())))
+    }
+
+    fn parse_rows<R: BufRead>(reader: R, parse_row: impl Fn(&str) -> Array1<u32>) -> Self {
         let rows = reader.lines()
             .filter_map(|line| line.ok())
-            .map(|line| Array::from_iter(line.chars().filter_map(|ch| ch.to_digit(10))))
+            .map(|line| parse_row(&line))
             .collect::<Vec<_>>();
         let views = rows.iter()
             .map(|row| row.view())
@@ -25,9 +59,33 @@ impl Forest {
         self.trees.indexed_iter()
             .map(|(index, &height)| Tree::new(&self.trees, index, height))
     }
+
+    /// Returns the number of trees on the perimeter, which are always visible.
+    pub fn edge_count(&self) -> usize {
+        let (w, h) = self.trees.dim();
+
+        if w < 2 || h < 2 {
+            w * h
+        } else {
+            2 * (w + h) - 4
+        }
+    }
+
+    pub fn interior_visible_count(&self) -> usize {
+        let (w, h) = self.trees.dim();
+
+        self.all()
+            .filter(|tree| {
+                let (i, j) = tree.index;
+
+                i > 0 && j > 0 && i + 1 < w && j + 1 < h
+            })
+            .filter(|tree| tree.is_visible())
+            .count()
+    }
 }
 
-struct Tree<'a> {
+pub struct Tree<'a> {
     forest: &'a Array2<u32>,
     index: (usize, usize),
     height: u32
@@ -39,12 +97,21 @@ impl<'a> Tree<'a> {
     }
 
     fn is_visible(&self) -> bool {
-        let (i, j) = self.index;
+        self.visible_from() != Directions::NONE
+    }
 
-        self.forest.slice(s![..i, j]).iter().all(|&other| other < self.height)
-            || self.forest.slice(s![(i+1).., j]).iter().all(|&other| other < self.height)
-            || self.forest.slice(s![i, ..j]).iter().all(|&other| other < self.height)
-            || self.forest.slice(s![i, (j+1)..]).iter().all(|&other| other < self.height)
+    pub fn visible_from(&self) -> Directions {
+        let (i, j) = self.index;
+        let is_shorter = |other: &u32| *other < self.height;
+
+        [
+            (self.forest.slice(s![..i, j]).iter().all(is_shorter), Directions::LEFT),
+            (self.forest.slice(s![(i+1).., j]).iter().all(is_shorter), Directions::RIGHT),
+            (self.forest.slice(s![i, ..j]).iter().all(is_shorter), Directions::TOP),
+            (self.forest.slice(s![i, (j+1)..]).iter().all(is_shorter), Directions::BOTTOM),
+        ].into_iter()
+            .filter(|(visible, _)| *visible)
+            .fold(Directions::NONE, |acc, (_, direction)| acc | direction)
     }
 
     fn view_distance(&self, iter: impl Iterator<Item = &'a u32>) -> usize {
@@ -97,6 +164,44 @@ mod tests {
         assert_eq!(forest.all().filter(|tree| tree.is_visible()).count(), 21);
     }
 
+    #[test]
+    fn _01_edge_count() {
+        let forest = Forest::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(forest.edge_count(), 16);
+        assert_eq!(forest.interior_visible_count(), 5);
+        assert_eq!(forest.edge_count() + forest.interior_visible_count(), forest.all().filter(|tree| tree.is_visible()).count());
+        assert_eq!(Forest::parse_all(Cursor::new("123")).edge_count(), 3);
+    }
+
+    #[test]
+    fn _01_visible_from() {
+        let forest = Forest::parse_all(Cursor::new(EXAMPLE));
+        let tree_at = |x: usize, y: usize| forest.all().find(|tree| tree.index == (x, y)).unwrap();
+
+        assert_eq!(tree_at(0, 1).visible_from(), Directions::LEFT);
+        assert_eq!(tree_at(1, 1).visible_from(), Directions::LEFT | Directions::TOP);
+        assert_eq!(tree_at(1, 2).visible_from(), Directions::RIGHT);
+        assert_eq!(tree_at(3, 1).visible_from(), Directions::NONE);
+        assert!(tree_at(0, 0).visible_from().contains(Directions::TOP | Directions::LEFT));
+    }
+
+    #[test]
+    fn _01_parse_spaced() {
+        let spaced = EXAMPLE.lines()
+            .map(|line| line.chars().map(|ch| ch.to_string()).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let forest = Forest::parse_spaced(Cursor::new(spaced));
+        let tall = Forest::parse_spaced(Cursor::new("10 2 15\n4 12 6\n7 11 9"));
+
+        assert_eq!(forest.trees, Forest::parse_all(Cursor::new(EXAMPLE)).trees);
+        assert_eq!(tall.trees.dim(), (3, 3));
+        assert_eq!(tall.trees[(2, 0)], 15);
+        assert_eq!(tall.all().filter(|tree| tree.is_visible()).count(), 9);
+        assert_eq!(tall.all().map(|tree| tree.scenic_score()).max(), Some(1));
+    }
+
     #[test]
     fn _02_example() {
         let forest = Forest::parse_all(Cursor::new(EXAMPLE));
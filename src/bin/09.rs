@@ -1,9 +1,8 @@
-use sscanf::sscanf;
-use std::io::{prelude::*, Cursor, stdin};
+use std::io::{prelude::*, stdin};
 use std::collections::HashSet;
 
-struct Rope {
-    visited: HashSet<(isize, isize)>,
+pub struct Rope {
+    visited: Vec<HashSet<(isize, isize)>>,
     knots: Vec<(isize, isize)>
 }
 
@@ -12,17 +11,26 @@ impl Rope {
         let mut rope = Self::new(num_knots);
 
         for line in reader.lines().filter_map(|line| line.ok()) {
-            if let Ok(n) = sscanf!(line, "R {}", usize) {
-                for _ in 0..n { rope.move_relative(1, 0) }
-            } else if let Ok(n) = sscanf!(line, "L {}", usize) {
-                for _ in 0..n { rope.move_relative(-1, 0) }
-            } else if let Ok(n) = sscanf!(line, "U {}", usize) {
-                for _ in 0..n { rope.move_relative(0, 1) }
-            } else if let Ok(n) = sscanf!(line, "D {}", usize) {
-                for _ in 0..n { rope.move_relative(0, -1) }
-            } else {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+            if tokens.is_empty() || tokens.len() % 2 != 0 {
                 panic!("unrecognized line -- {}", line)
             }
+
+            // a line may hold several `direction count` pairs, which are
+            // applied left to right
+            for pair in tokens.chunks(2) {
+                let (dx, dy) = match pair[0] {
+                    "R" => (1, 0),
+                    "L" => (-1, 0),
+                    "U" => (0, 1),
+                    "D" => (0, -1),
+                    _ => panic!("unrecognized line -- {}", line)
+                };
+                let n = pair[1].parse::<usize>().unwrap_or_else(|_| panic!("unrecognized line -- {}", line));
+
+                for _ in 0..n { rope.move_relative(dx, dy) }
+            }
         }
 
         rope
@@ -30,7 +38,7 @@ impl Rope {
 
     fn new(num_knots: usize) -> Self {
         Self {
-            visited: HashSet::from_iter([(0, 0)].into_iter()),
+            visited: vec! [HashSet::from_iter([(0, 0)].into_iter()); num_knots],
             knots: vec![(0, 0)].repeat(num_knots),
         }
     }
@@ -39,13 +47,15 @@ impl Rope {
         if let Some(head) = self.knots.first_mut() {
             head.0 += dx;
             head.1 += dy;
+            self.visited[0].insert(*head);
 
             for i in 1..self.knots.len() {
                 Self::adjust_knot(self.knots[i-1], &mut self.knots[i]);
+                self.visited[i].insert(self.knots[i]);
             }
-
-            self.visited.insert(*self.knots.last().unwrap());
         }
+
+        debug_assert!(self.is_valid());
     }
 
     fn adjust_knot(head: (isize, isize), tail: &mut (isize, isize)) {
@@ -62,23 +72,46 @@ impl Rope {
         }
     }
 
-    fn num_visited(&self) -> usize {
-        self.visited.len()
+    /// Returns if every knot touches the knot before it, i.e. is within a
+    /// Chebyshev distance of one.
+    pub fn is_valid(&self) -> bool {
+        self.knots.windows(2).all(|pair| {
+            (pair[0].0 - pair[1].0).abs() <= 1 && (pair[0].1 - pair[1].1).abs() <= 1
+        })
+    }
+
+    pub fn num_visited(&self) -> usize {
+        self.visited.last().map(|visited| visited.len()).unwrap_or(0)
+    }
+
+    pub fn knot_positions(&self) -> &[(isize, isize)] {
+        &self.knots
     }
 }
 
+/// Returns the number of positions visited by the tail of a rope with `2`,
+/// `3`, ..., `max_knots` knots. The first `k` knots of a longer rope move
+/// exactly like a rope with `k` knots, so a single rope is simulated.
+fn simulate_all<R: BufRead>(reader: R, max_knots: usize) -> Vec<usize> {
+    let rope = Rope::parse_all(reader, max_knots);
+
+    rope.visited.iter()
+        .skip(1)
+        .map(|visited| visited.len())
+        .collect()
+}
+
 fn main() {
-    let mut movement = String::new();
+    let visited = simulate_all(stdin().lock(), 10);
 
-    if let Ok(_) = stdin().lock().read_to_string(&mut movement) {
-        println!("{}", Rope::parse_all(Cursor::new(&movement), 2).num_visited());
-        println!("{}", Rope::parse_all(Cursor::new(&movement), 10).num_visited());
-    }
+    println!("{}", visited[0]);
+    println!("{}", visited[8]);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Cursor;
 
     const EXAMPLE: &str = r#"R 4
 U 4
@@ -105,6 +138,30 @@ U 20"#;
         assert_eq!(rope.num_visited(), 13);
     }
 
+    #[test]
+    fn _01_single_knot() {
+        let rope = Rope::parse_all(Cursor::new("R 4\nU 2\nL 4"), 1);
+
+        assert_eq!(rope.num_visited(), 11);
+        assert_eq!(Rope::parse_all(Cursor::new(EXAMPLE), 10).visited[0], Rope::parse_all(Cursor::new(EXAMPLE), 1).visited[0]);
+    }
+
+    #[test]
+    fn _01_knot_positions() {
+        let rope = Rope::parse_all(Cursor::new(EXAMPLE), 2);
+
+        assert_eq!(rope.knot_positions(), &[(2, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn _01_combined_moves() {
+        let combined = Rope::parse_all(Cursor::new("R 4 U 2\nL 3 D 1 R 5"), 2);
+        let separate = Rope::parse_all(Cursor::new("R 4\nU 2\nL 3\nD 1\nR 5"), 2);
+
+        assert_eq!(combined.visited, separate.visited);
+        assert_eq!(combined.knot_positions(), separate.knot_positions());
+    }
+
     #[test]
     fn _02_example() {
         let rope = Rope::parse_all(Cursor::new(EXAMPLE), 9);
@@ -118,4 +175,30 @@ U 20"#;
 
         assert_eq!(rope.num_visited(), 36);
     }
+
+    #[test]
+    fn _02_is_valid() {
+        // `move_relative` also checks this after every step in debug builds
+        for num_knots in [2, 10] {
+            assert!(Rope::parse_all(Cursor::new(LARGE_EXAMPLE), num_knots).is_valid());
+        }
+
+        let mut rope = Rope::new(3);
+        rope.knots[1] = (2, 1);
+        assert!(!rope.is_valid());
+    }
+
+    #[test]
+    fn _02_simulate_all() {
+        let visited = simulate_all(Cursor::new(EXAMPLE), 10);
+
+        assert_eq!(visited.len(), 9);
+        assert_eq!(visited[0], 13);
+        assert_eq!(visited[8], 1);
+        assert_eq!(simulate_all(Cursor::new(LARGE_EXAMPLE), 10)[8], 36);
+
+        for knots in 2..=10 {
+            assert_eq!(visited[knots - 2], Rope::parse_all(Cursor::new(EXAMPLE), knots).num_visited());
+        }
+    }
 }
@@ -1,6 +1,10 @@
+use ndarray::Array2;
 use sscanf::sscanf;
 use std::io::{prelude::*, stdin};
 
+const SCREEN_HEIGHT: usize = 6;
+const SCREEN_WIDTH: usize = 40;
+
 #[derive(Clone, Copy)]
 enum Instruction {
     AddX(isize),
@@ -83,6 +87,15 @@ impl Program {
         self.cpu.cycles()
     }
 
+    fn signal_strength_sum(&self, first: usize, step: usize) -> isize {
+        self.cycles()
+            .enumerate()
+            .skip(first)
+            .step_by(step)
+            .map(|(cycle, signal_strength)| cycle as isize * signal_strength)
+            .sum()
+    }
+
     #[cfg(test)]
     fn current_cycle(&self) -> usize {
         self.cpu.current_cycle()
@@ -93,33 +106,43 @@ impl Program {
         self.cpu.current_value()
     }
 
-    fn screen(&self) -> String {
-        let mut output = String::new();
-
-        for (i, signal) in self.cycles().skip(1).enumerate() {
-            let position = (i % 40) as isize;
+    /// Returns which pixels of the screen are lit, cycles that do not fit on
+    /// the screen are ignored.
+    fn pixels(&self) -> Array2<bool> {
+        let mut pixels = Array2::from_elem((SCREEN_HEIGHT, SCREEN_WIDTH), false);
 
-            if i > 0 && position == 0 {
-                output += "\n";
-            }
+        for (i, signal) in self.cycles().skip(1).enumerate().take(SCREEN_HEIGHT * SCREEN_WIDTH) {
+            let position = (i % SCREEN_WIDTH) as isize;
 
-            output += if signal >= position - 1 && signal <= position + 1 {
-                "#"
-            } else {
-                "."
-            };
+            pixels[(i / SCREEN_WIDTH, i % SCREEN_WIDTH)] = signal >= position - 1 && signal <= position + 1;
         }
 
-        output
+        pixels
+    }
+
+    fn screen(&self) -> String {
+        self.pixels().rows()
+            .into_iter()
+            .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
+pub fn run_with_breakpoints<R: BufRead>(reader: R, cycles: &[usize]) -> Vec<(usize, isize)> {
+    let prog = Program::parse_all(reader);
+    let history = prog.cycles().collect::<Vec<_>>();
+
+    cycles.iter()
+        .map(|&cycle| (cycle, history.get(cycle).cloned().unwrap_or(prog.cpu.current_value())))
+        .collect()
+}
 
 fn main() {
     let stdin = stdin().lock();
     let prog = Program::parse_all(stdin);
 
-    println!("{}", prog.cycles().enumerate().skip(20).step_by(40).map(|(cycle, signal_strength)| cycle as isize * signal_strength).sum::<isize>());
+    println!("{}", prog.signal_strength_sum(20, 40));
     println!("{}", prog.screen());
 }
 
@@ -294,6 +317,23 @@ noop"#;
         assert_eq!(prog.cycles().enumerate().skip(20).step_by(40).map(|(cycle, signal_strength)| cycle as isize * signal_strength).sum::<isize>(), 13140);
     }
 
+    #[test]
+    fn _01_signal_strength_sum() {
+        let prog = Program::parse_all(Cursor::new(LARGE_EXAMPLE));
+
+        assert_eq!(prog.signal_strength_sum(20, 40), 13140);
+        assert_eq!(prog.signal_strength_sum(20, 1000), 420);
+    }
+
+    #[test]
+    fn _01_run_with_breakpoints() {
+        let breakpoints = run_with_breakpoints(Cursor::new(LARGE_EXAMPLE), &[20, 60, 100, 140, 180, 220]);
+
+        assert_eq!(breakpoints, vec! [(20, 21), (60, 19), (100, 18), (140, 21), (180, 16), (220, 18)]);
+        assert_eq!(breakpoints.iter().map(|&(cycle, value)| cycle as isize * value).sum::<isize>(), 13140);
+        assert_eq!(run_with_breakpoints(Cursor::new(EXAMPLE), &[5, 1, 1000]), vec! [(5, 4), (1, 1), (1000, -1)]);
+    }
+
     #[test]
     fn _02_example() {
         let prog = Program::parse_all(Cursor::new(LARGE_EXAMPLE));
@@ -305,4 +345,16 @@ noop"#;
 ######......######......######......####
 #######.......#######.......#######....."#);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn _02_pixels() {
+        let pixels = Program::parse_all(Cursor::new(LARGE_EXAMPLE)).pixels();
+
+        assert_eq!(pixels.dim(), (6, 40));
+        assert!(pixels[(0, 0)]);
+        assert!(!pixels[(0, 2)]);
+        assert!(pixels[(5, 6)]);
+        assert!(!pixels[(5, 7)]);
+        assert_eq!(pixels.iter().filter(|&&lit| lit).count(), 124);
+    }
+}
@@ -19,18 +19,44 @@ impl Op {
             Self::Sq => lhs * lhs
         }
     }
+
+    fn modulus(&self) -> Option<isize> {
+        match *self {
+            Self::Mod { rhs } => Some(rhs),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum TestOp {
+    DivisibleBy { rhs: isize },
+}
+
+impl TestOp {
+    fn matches(&self, worry_level: isize) -> bool {
+        match *self {
+            Self::DivisibleBy { rhs } => worry_level % rhs == 0
+        }
+    }
+
+    fn modulus(&self) -> isize {
+        match *self {
+            Self::DivisibleBy { rhs } => rhs
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 struct Test {
-    check: Op,
+    check: TestOp,
     if_true: usize,
     if_false: usize,
 }
 
 impl Test {
     fn target_monkey(&self, worry_level: isize) -> usize {
-        if self.check.execute(worry_level) == 0 {
+        if self.check.matches(worry_level) {
             self.if_true
         } else {
             self.if_false
@@ -39,7 +65,7 @@ impl Test {
 }
 
 #[derive(Clone, Debug)]
-struct Monkey {
+pub struct Monkey {
     inspected_items: usize,
     items: Vec<isize>,
     operation: Op,
@@ -61,7 +87,7 @@ impl Monkey {
                 }
             } else if let Ok(rhs) = sscanf!(line, "  Test: divisible by {}", isize) {
                 test = Some(Test {
-                    check: Op::Mod { rhs },
+                    check: TestOp::DivisibleBy { rhs },
                     if_true: 0,
                     if_false: 0,
                 });
@@ -83,6 +109,10 @@ impl Monkey {
                 operation = Some(Op::Add { rhs });
             } else if let Ok(rhs) = sscanf!(line, "  Operation: new = old - {}", isize) {
                 operation = Some(Op::Add { rhs: -rhs });
+            } else if let Ok(rhs) = sscanf!(line, "  Operation: new = old % {}", isize) {
+                operation = Some(Op::Mod { rhs });
+            } else if line.starts_with('#') {
+                // configuration header, see `parse_config`
             } else if line.is_empty() {
                 break
             }
@@ -106,11 +136,10 @@ impl Monkey {
         monkeys
     }
 
-    fn safe_modulus(&self) -> isize {
-        match self.test.check {
-            Op::Mod { rhs } => rhs,
-            _ => panic!()
-        }
+    /// Returns every modulus this monkey applies to a worry level, a common
+    /// multiple of which can be used to keep worry levels small.
+    fn moduli(&self) -> impl Iterator<Item=isize> {
+        std::iter::once(self.test.check.modulus()).chain(self.operation.modulus())
     }
 
     fn drain_items(&mut self, relief: &impl Fn(isize) -> isize) -> Vec<(isize, usize)> {
@@ -131,12 +160,117 @@ impl Monkey {
     }
 }
 
-fn execute_round(monkeys: &mut [Monkey], relief: impl Fn(isize) -> isize) {
+/// Returns how many items each monkey inspected during this round.
+fn execute_round(monkeys: &mut [Monkey], relief: impl Fn(isize) -> isize) -> Vec<usize> {
+    let mut inspected = vec! [0; monkeys.len()];
+
     for i in 0..monkeys.len() {
-        for (worry_level, to_monkey) in monkeys[i].drain_items(&relief) {
+        let thrown = monkeys[i].drain_items(&relief);
+        inspected[i] = thrown.len();
+
+        for (worry_level, to_monkey) in thrown {
             monkeys[to_monkey].push(worry_level);
         }
     }
+
+    inspected
+}
+
+/// Same as `execute_round`, but moves each item straight into the target
+/// monkey instead of collecting the thrown items first.
+fn execute_round_inplace(monkeys: &mut [Monkey], relief: impl Fn(isize) -> isize) {
+    for i in 0..monkeys.len() {
+        let items = std::mem::take(&mut monkeys[i].items);
+        monkeys[i].inspected_items += items.len();
+
+        for worry_level in items {
+            let new_worry_level = relief(monkeys[i].operation.execute(worry_level));
+            let to_monkey = monkeys[i].test.target_monkey(new_worry_level);
+
+            monkeys[to_monkey].push(new_worry_level);
+        }
+    }
+}
+
+/// Returns `(round, monkey, inspected_items)` for every monkey in every round.
+pub fn execute_rounds_traced(monkeys: &mut [Monkey], rounds: usize, relief: impl Fn(isize) -> isize) -> Vec<(usize, usize, usize)> {
+    let mut trace = vec! [];
+
+    for round in 0..rounds {
+        for (monkey, inspected) in execute_round(monkeys, &relief).into_iter().enumerate() {
+            trace.push((round, monkey, inspected));
+        }
+    }
+
+    trace
+}
+
+fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Returns the least common multiple of every monkey's divisibility check and
+/// modulus operation.
+fn common_modulus(monkeys: &[Monkey]) -> isize {
+    monkeys.iter()
+        .flat_map(|monkey| monkey.moduli())
+        .fold(1, |so_far, modulus| so_far / gcd(so_far, modulus) * modulus)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relief {
+    /// Divide the worry level by three after every inspection.
+    Div3,
+    /// Reduce the worry level by the common modulus of all monkeys.
+    Modulus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunConfig {
+    pub rounds: usize,
+    pub relief: Relief,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self { rounds: 20, relief: Relief::Div3 }
+    }
+}
+
+/// Reads an optional `# rounds=500 relief=div3` header before the monkeys,
+/// returning the part one settings if there is none, and `None` if the header
+/// is malformed.
+fn parse_config<R: BufRead>(reader: &mut R) -> Option<RunConfig> {
+    let mut config = RunConfig::default();
+
+    if reader.fill_buf().ok()?.first() != Some(&b'#') {
+        return Some(config);
+    }
+
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+
+    for part in line[1..].split_whitespace() {
+        match part.split_once('=')? {
+            ("rounds", rounds) => config.rounds = rounds.parse().ok()?,
+            ("relief", "div3") => config.relief = Relief::Div3,
+            ("relief", "mod") => config.relief = Relief::Modulus,
+            _ => return None
+        }
+    }
+
+    Some(config)
+}
+
+fn run(monkeys: &mut [Monkey], config: RunConfig) {
+    let total_mod = common_modulus(monkeys);
+
+    for _ in 0..config.rounds {
+        match config.relief {
+            Relief::Div3 => execute_round_inplace(monkeys, |worry_level| worry_level / 3),
+            Relief::Modulus => execute_round_inplace(monkeys, |worry_level| worry_level % total_mod),
+        }
+    }
 }
 
 fn monkey_business(mut inspected_items: Vec<usize>) -> usize {
@@ -152,12 +286,12 @@ fn monkey_business(mut inspected_items: Vec<usize>) -> usize {
 }
 
 fn main() {
-    let stdin = stdin().lock();
+    let mut stdin = stdin().lock();
+    let config = parse_config(&mut stdin).expect("invalid configuration header");
     let mut monkeys1 = Monkey::parse_all(stdin);
     let mut monkeys2 = monkeys1.clone();
-    let total_mod = monkeys2.iter().map(|monkey| monkey.safe_modulus()).product::<isize>();
-    for _ in 0..20 { execute_round(&mut monkeys1, |worry_level| worry_level / 3); }
-    for _ in 0..10000 { execute_round(&mut monkeys2, |worry_level| worry_level % total_mod); }
+    run(&mut monkeys1, config);
+    run(&mut monkeys2, RunConfig { rounds: 10000, relief: Relief::Modulus });
 
     println!("{}", monkey_business(monkeys1.iter().map(|monkey| monkey.inspected_items()).collect()));
     println!("{}", monkey_business(monkeys2.iter().map(|monkey| monkey.inspected_items()).collect()));
@@ -209,10 +343,100 @@ Monkey 3:
         assert_eq!(monkey_business(monkeys.iter().map(|monkey| monkey.inspected_items()).collect()), 10605);
     }
 
+    #[test]
+    fn _01_trace() {
+        let mut monkeys = Monkey::parse_all(Cursor::new(EXAMPLE));
+        let trace = execute_rounds_traced(&mut monkeys, 20, |worry_level| worry_level / 3);
+        let total_for = |monkey: usize| {
+            trace.iter().filter(|(_, m, _)| *m == monkey).map(|(_, _, inspected)| inspected).sum::<usize>()
+        };
+
+        assert_eq!(trace.len(), 80);
+        assert_eq!(&trace[0..4], &[(0, 0, 2), (0, 1, 4), (0, 2, 3), (0, 3, 5)]);
+        assert_eq!([total_for(0), total_for(1), total_for(2), total_for(3)], [101, 95, 7, 105]);
+        assert_eq!(monkeys.iter().map(|monkey| monkey.inspected_items()).collect::<Vec<_>>(), vec! [101, 95, 7, 105]);
+    }
+
+    #[test]
+    fn _02_execute_round_inplace() {
+        let mut monkeys = Monkey::parse_all(Cursor::new(EXAMPLE));
+        let mut inplace = monkeys.clone();
+        let total_mod = common_modulus(&monkeys);
+
+        for _ in 0..20 {
+            execute_round(&mut monkeys, |worry_level| worry_level / 3);
+            execute_round_inplace(&mut inplace, |worry_level| worry_level / 3);
+        }
+
+        assert_eq!(inplace.iter().map(|monkey| monkey.items.clone()).collect::<Vec<_>>(), monkeys.iter().map(|monkey| monkey.items.clone()).collect::<Vec<_>>());
+        assert_eq!(monkey_business(inplace.iter().map(|monkey| monkey.inspected_items()).collect()), 10605);
+
+        let mut inplace = Monkey::parse_all(Cursor::new(EXAMPLE));
+        for _ in 0..10000 { execute_round_inplace(&mut inplace, |worry_level| worry_level % total_mod); }
+
+        assert_eq!(monkey_business(inplace.iter().map(|monkey| monkey.inspected_items()).collect()), 2713310158);
+    }
+
+    #[test]
+    fn _02_common_modulus() {
+        let mut monkeys = Monkey::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(common_modulus(&monkeys), 23 * 19 * 13 * 17);
+
+        monkeys[1].test.check = TestOp::DivisibleBy { rhs: 46 };
+        assert_eq!(common_modulus(&monkeys), 46 * 13 * 17);
+
+        monkeys[2].operation = Op::Mod { rhs: 7 };
+        assert_eq!(common_modulus(&monkeys), 46 * 13 * 17 * 7);
+    }
+
+    #[test]
+    fn _02_modulus_operation() {
+        let with_mod = EXAMPLE.replacen("new = old + 6", "new = old % 7", 1);
+        let mut monkeys = Monkey::parse_all(Cursor::new(&with_mod));
+        let mut expected = Monkey::parse_all(Cursor::new(&with_mod));
+        let total_mod = common_modulus(&monkeys);
+
+        assert_eq!(total_mod, 23 * 19 * 13 * 17 * 7);
+
+        for _ in 0..20 {
+            execute_round_inplace(&mut monkeys, |worry_level| worry_level % total_mod);
+            execute_round_inplace(&mut expected, |worry_level| worry_level);
+        }
+
+        assert_eq!(
+            monkeys.iter().map(|monkey| monkey.inspected_items()).collect::<Vec<_>>(),
+            expected.iter().map(|monkey| monkey.inspected_items()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn _03_parse_config() {
+        let mut plain = Cursor::new(EXAMPLE);
+        let mut with_header = Cursor::new(format!("# rounds=10000 relief=mod\n{}", EXAMPLE));
+        let plain_config = parse_config(&mut plain).unwrap();
+        let header_config = parse_config(&mut with_header).unwrap();
+        let mut plain_monkeys = Monkey::parse_all(plain);
+        let mut header_monkeys = Monkey::parse_all(with_header);
+
+        assert_eq!(plain_config, RunConfig { rounds: 20, relief: Relief::Div3 });
+        assert_eq!(header_config, RunConfig { rounds: 10000, relief: Relief::Modulus });
+        assert_eq!(header_monkeys.len(), 4);
+
+        run(&mut plain_monkeys, plain_config);
+        run(&mut header_monkeys, header_config);
+        assert_eq!(monkey_business(plain_monkeys.iter().map(|monkey| monkey.inspected_items()).collect()), 10605);
+        assert_eq!(monkey_business(header_monkeys.iter().map(|monkey| monkey.inspected_items()).collect()), 2713310158);
+        assert_eq!(Monkey::parse_all(Cursor::new(format!("# rounds=500 relief=div3\n{}", EXAMPLE))).len(), 4);
+        assert_eq!(parse_config(&mut Cursor::new("# rounds=500")), Some(RunConfig { rounds: 500, relief: Relief::Div3 }));
+        assert_eq!(parse_config(&mut Cursor::new("# rounds=many")), None);
+        assert_eq!(parse_config(&mut Cursor::new("# relief=none")), None);
+    }
+
     #[test]
     fn _02_example() {
         let mut monkeys = Monkey::parse_all(Cursor::new(EXAMPLE));
-        let total_mod = monkeys.iter().map(|monkey| monkey.safe_modulus()).product::<isize>();
+        let total_mod = common_modulus(&monkeys);
         for _ in 0..10000 { execute_round(&mut monkeys, |worry_level| worry_level % total_mod); }
 
         assert_eq!(monkeys.len(), 4);
@@ -2,7 +2,7 @@ use ndarray::prelude::*;
 use std::io::{prelude::*, stdin};
 use std::collections::VecDeque;
 
-struct HMap {
+pub struct HMap {
     raw_values: Array2<char>,
     heights: Array2<usize>,
 }
@@ -55,18 +55,38 @@ impl HMap {
     }
 
     fn shortest_paths(&self, starting_point: (usize, usize)) -> Array2<usize> {
+        self.shortest_paths_with(starting_point, |from_height, to_height| to_height <= from_height + 1)
+    }
+
+    /// Returns the shortest distance from `starting_point` to every point when
+    /// `can_move(from_height, to_height)` decides whether a step is allowed.
+    pub fn shortest_paths_with(&self, starting_point: (usize, usize), can_move: impl Fn(usize, usize) -> bool) -> Array2<usize> {
+        self.shortest_paths_from([starting_point], can_move)
+    }
+
+    /// Returns the shortest distance from the closest of `starting_points` to
+    /// every point, as if they were all a single point at distance zero.
+    fn shortest_paths_from(
+        &self,
+        starting_points: impl IntoIterator<Item=(usize, usize)>,
+        can_move: impl Fn(usize, usize) -> bool
+    ) -> Array2<usize>
+    {
         let shape  = self.heights.dim();
         let mut shortest_so_far = Array2::from_elem(shape, usize::MAX);
         let mut to_visit = VecDeque::new();
-        to_visit.push_back(starting_point);
-        shortest_so_far[starting_point] = 0;
+
+        for starting_point in starting_points {
+            to_visit.push_back(starting_point);
+            shortest_so_far[starting_point] = 0;
+        }
 
         while let Some(point) = to_visit.pop_front() {
             let curr_distance = shortest_so_far[point];
             let curr_height = self.heights[point];
 
             for neighbour in self.neighbours(point) {
-                if self.heights[neighbour] <= curr_height + 1 && shortest_so_far[neighbour] > curr_distance + 1 {
+                if can_move(curr_height, self.heights[neighbour]) && shortest_so_far[neighbour] > curr_distance + 1 {
                     shortest_so_far[neighbour] = curr_distance + 1;
                     to_visit.push_back(neighbour);
                 }
@@ -75,6 +95,35 @@ impl HMap {
 
         shortest_so_far
     }
+
+    /// Returns the elevation-0 starting point closest to the goal, and its
+    /// distance, by searching backwards from the goal once.
+    fn best_trailhead(&self) -> Option<((usize, usize), usize)> {
+        let distance_to_goal = self.shortest_paths_with(self.goal_point(), |from_height, to_height| from_height <= to_height + 1);
+
+        self.possible_starting_points()
+            .map(|starting_point| (starting_point, distance_to_goal[starting_point]))
+            .filter(|&(_, distance)| distance != usize::MAX)
+            .min_by_key(|&(_, distance)| distance)
+    }
+
+    /// Returns the shortest distance from any elevation-0 point to the goal, by
+    /// searching forward from all of them at once. This is only valid because
+    /// every source shares the same goal, so the distance of the goal is the
+    /// minimum over all sources. It says nothing about which source was used,
+    /// nor the distance from any particular one.
+    pub fn shortest_from_any_low(&self) -> usize {
+        let distances = self.shortest_paths_from(
+            self.possible_starting_points(),
+            |from_height, to_height| to_height <= from_height + 1
+        );
+
+        distances[self.goal_point()]
+    }
+
+    pub fn num_reachable(&self, starting_point: (usize, usize)) -> usize {
+        self.shortest_paths(starting_point).iter().filter(|&&distance| distance != usize::MAX).count()
+    }
 }
 
 fn main() {
@@ -83,7 +132,7 @@ fn main() {
     let goal_point = hmap.goal_point();
 
     println!("{}", hmap.shortest_paths(hmap.starting_point())[goal_point]);
-    println!("{}", hmap.possible_starting_points().map(|starting_point| hmap.shortest_paths(starting_point)[goal_point]).min().unwrap());
+    println!("{}", hmap.best_trailhead().unwrap().1);
 }
 
 #[cfg(test)]
@@ -105,6 +154,25 @@ abdefghi"#;
         assert_eq!(min_distance_to[hmap.goal_point()], 31);
     }
 
+    #[test]
+    fn _01_shortest_paths_with() {
+        let hmap = HMap::parse(Cursor::new(EXAMPLE));
+        let default = hmap.shortest_paths_with(hmap.starting_point(), |from_height, to_height| to_height <= from_height + 1);
+        let drone = hmap.shortest_paths_with(hmap.starting_point(), |from_height, to_height| to_height + 2 >= from_height);
+
+        assert_eq!(default, hmap.shortest_paths(hmap.starting_point()));
+        assert_eq!(default[hmap.goal_point()], 31);
+        assert_eq!(drone[hmap.goal_point()], 7);
+    }
+
+    #[test]
+    fn _01_num_reachable() {
+        let hmap = HMap::parse(Cursor::new(EXAMPLE));
+
+        assert_eq!(hmap.num_reachable(hmap.starting_point()), 40);
+        assert_eq!(hmap.num_reachable(hmap.goal_point()), 40);
+    }
+
     #[test]
     fn _02_example() {
         let hmap = HMap::parse(Cursor::new(EXAMPLE));
@@ -112,4 +180,22 @@ abdefghi"#;
 
         assert_eq!(hmap.possible_starting_points().map(|starting_point| hmap.shortest_paths(starting_point)[goal_point]).min(), Some(29));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn _02_best_trailhead() {
+        let hmap = HMap::parse(Cursor::new(EXAMPLE));
+        let (starting_point, distance) = hmap.best_trailhead().unwrap();
+
+        assert_eq!(distance, 29);
+        assert_eq!(hmap.heights[starting_point], 0);
+        assert_eq!(hmap.shortest_paths(starting_point)[hmap.goal_point()], 29);
+    }
+
+    #[test]
+    fn _02_shortest_from_any_low() {
+        let hmap = HMap::parse(Cursor::new(EXAMPLE));
+
+        assert_eq!(hmap.shortest_from_any_low(), 29);
+        assert_eq!(hmap.shortest_from_any_low(), hmap.best_trailhead().unwrap().1);
+    }
+}
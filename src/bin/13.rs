@@ -1,17 +1,28 @@
 use std::io::{prelude::*, stdin};
 use std::cmp::Ordering;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 enum Packet {
     Array(Vec<Packet>),
     Int(i64),
 }
 
+/// Construct a `Packet` from the same notation as the input, e.g.
+/// `packet!([1, [2, 3]])`.
+macro_rules! packet {
+    ([$($element:tt),*]) => {
+        Packet::Array(vec! [$(packet!($element)),*])
+    };
+    ($value:expr) => {
+        Packet::Int($value)
+    };
+}
+
 impl Packet {
     fn dividers() -> Vec<Self> {
         vec! [
-            Packet::Array(vec! [Packet::Array(vec! [Packet::Int(2)])]),
-            Packet::Array(vec! [Packet::Array(vec! [Packet::Int(6)])]),
+            packet!([[2]]),
+            packet!([[6]]),
         ]
     }
 
@@ -22,16 +33,24 @@ impl Packet {
     }
 
     fn parse_all<R: BufRead>(reader: R) -> Vec<Self> {
+        Self::iter_packets(reader).collect::<Vec<_>>()
+    }
+
+    /// Lazily parse one packet per non-blank line of `reader`.
+    fn iter_packets<R: BufRead>(reader: R) -> impl Iterator<Item=Self> {
         reader.lines()
             .filter_map(|line| line.ok().filter(|line| !line.is_empty()))
             .map(|line| Self::parse(&line))
-            .collect::<Vec<_>>()
     }
 
     fn parse(line: &str) -> Self {
         Packet::from(&serde_json::from_str::<serde_json::Value>(line).unwrap())
     }
 
+    fn is_sorted(packets: &[Packet]) -> bool {
+        packets.windows(2).all(|pair| pair[0].compare_to(&pair[1]) != Ordering::Greater)
+    }
+
     fn compare_to(&self, right: &Self) -> Ordering {
         match (self, right) {
             (left @ Self::Int(_), right @ Self::Array(_)) => Self::Array(vec! [left.clone()]).compare_to(right),
@@ -58,6 +77,29 @@ impl From<&serde_json::Value> for Packet {
     }
 }
 
+#[cfg(feature = "parallel")]
+fn sum_ordered_indices_parallel(chunks: &[(Packet, Packet)]) -> usize {
+    let num_threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = chunks.len().div_ceil(num_threads).max(1);
+
+    std::thread::scope(|scope| {
+        let handles = chunks.chunks(chunk_size)
+            .enumerate()
+            .map(|(n, part)| {
+                scope.spawn(move || {
+                    part.iter()
+                        .enumerate()
+                        .filter(|(_, pair)| pair.0.compare_to(&pair.1) == Ordering::Less)
+                        .map(|(i, _)| n * chunk_size + i + 1)
+                        .sum::<usize>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+    })
+}
+
 fn main() {
     let stdin = stdin().lock();
     let mut packets = Packet::parse_all(stdin);
@@ -65,7 +107,11 @@ fn main() {
     let dividers = Packet::dividers();
     packets.extend_from_slice(&dividers);
     packets.sort_by(|a, b| a.compare_to(b));
+    debug_assert!(Packet::is_sorted(&packets));
 
+    #[cfg(feature = "parallel")]
+    println!("{}", sum_ordered_indices_parallel(&chunks));
+    #[cfg(not(feature = "parallel"))]
     println!("{}", chunks.iter().enumerate().map(|(i, pair)| if pair.0.compare_to(&pair.1) == Ordering::Less { i + 1 } else { 0 }).sum::<usize>());
     println!("{}", packets.iter().enumerate().fold(1, |so_far, (i, packet)| if dividers.contains(packet) { so_far * (i + 1) } else { so_far }));
 }
@@ -116,13 +162,45 @@ mod tests {
         assert_eq!(chunks.iter().enumerate().map(|(i, pair)| if pair.0.compare_to(&pair.1) == Ordering::Less { i + 1 } else { 0 }).sum::<usize>(), 13);
     }
 
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn _01_sum_ordered_indices_parallel() {
+        let packets = Packet::parse_all(Cursor::new(EXAMPLE));
+        let chunks = Packet::split_chunks(&packets);
+
+        assert_eq!(sum_ordered_indices_parallel(&chunks), 13);
+        assert_eq!(sum_ordered_indices_parallel(&[]), 0);
+    }
+
+    #[test]
+    fn _01_iter_packets() {
+        let mut packets = Packet::iter_packets(Cursor::new(EXAMPLE));
+
+        assert_eq!(packets.next(), Some(packet!([1, 1, 3, 1, 1])));
+        assert_eq!(packets.next(), Some(packet!([1, 1, 5, 1, 1])));
+        assert_eq!(packets.next(), Some(packet!([[1], [2, 3, 4]])));
+        assert_eq!(Packet::iter_packets(Cursor::new(EXAMPLE)).collect::<Vec<_>>(), Packet::parse_all(Cursor::new(EXAMPLE)));
+        assert_eq!(Packet::iter_packets(Cursor::new("\n\n")).count(), 0);
+    }
+
+    #[test]
+    fn _01_packet_macro() {
+        assert_eq!(packet!([]), Packet::parse("[]"));
+        assert_eq!(packet!([1, [2, 3]]), Packet::parse("[1,[2,3]]"));
+        assert_eq!(packet!([1, [2, [3, [4, [5, 6, 7]]]], 8, 9]), Packet::parse("[1,[2,[3,[4,[5,6,7]]]],8,9]"));
+        assert_eq!(packet!([[[]]]), Packet::Array(vec! [Packet::Array(vec! [Packet::Array(vec! [])])]));
+    }
+
     #[test]
     fn _02_example() {
         let mut packets = Packet::parse_all(Cursor::new(EXAMPLE));
         let dividers = Packet::dividers();
         packets.extend_from_slice(&dividers);
+        assert!(!Packet::is_sorted(&packets));
         packets.sort_by(|a, b| a.compare_to(b));
 
+        assert!(Packet::is_sorted(&packets));
+        assert!(Packet::is_sorted(&packets[..0]));
         assert_eq!(packets.iter().position(|packet| packet == &dividers[0]).map(|i| i + 1), Some(10));
         assert_eq!(packets.iter().position(|packet| packet == &dividers[1]).map(|i| i + 1), Some(14));
         assert_eq!(packets.iter().enumerate().fold(1, |so_far, (i, packet)| if dividers.contains(packet) { so_far * (i + 1) } else { so_far }), 140);
@@ -50,8 +50,8 @@ impl Path {
     }
 }
 
-#[derive(Clone, Copy)]
-struct Sand(usize, usize);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sand(usize, usize);
 
 impl Sand {
     fn try_fall(self, check: impl Fn(usize, usize) -> bool) -> Option<Sand> {
@@ -69,7 +69,15 @@ impl Sand {
     }
 }
 
-struct Cave {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropResult {
+    Settled,
+    Blocked,
+    Abyss,
+    StalledAfter(usize),
+}
+
+pub struct Cave {
     structure: Array2<bool>
 }
 
@@ -93,37 +101,91 @@ impl Cave {
         self.structure.dim()
     }
 
+    /// The structure is anchored at the world origin with no offset, so this
+    /// is the identity: grid indices already are world coordinates.
+    pub fn to_world(&self, idx: (usize, usize)) -> (usize, usize) {
+        idx
+    }
+
+    /// Returns the grid index of `world`, or `None` if it lies outside the
+    /// cave. Since there is no offset the index equals `world` when present.
+    pub fn to_grid(&self, world: (usize, usize)) -> Option<(usize, usize)> {
+        let (width, height) = self.bounding_box();
+
+        if world.0 < width && world.1 < height {
+            Some(world)
+        } else {
+            None
+        }
+    }
+
     fn intersects(&self, point: (usize, usize)) -> bool {
         point.0 < self.structure.dim().0
             && point.1 < self.structure.dim().1
             && self.structure[point]
     }
 
-    fn drop_at(&mut self, mut starting_point: Sand) -> bool {
+    /// Returns `Ok` if the grain came to rest, otherwise why it did not
+    /// together with the last position inside the cave before it fell out
+    /// (`None` if it could not move at all).
+    fn drop_aux(&mut self, mut starting_point: Sand, max_steps: usize) -> Result<(), (DropResult, Option<Sand>)> {
         let bounding_box = self.structure.dim();
+        let mut last_point = None;
+        let mut steps = 0;
 
         while starting_point.0 < bounding_box.0 && starting_point.1 < bounding_box.1 {
             if let Some(new_point) = starting_point.try_fall(|i, j| self.intersects((i, j))) {
+                if steps == max_steps {
+                    return Err((DropResult::StalledAfter(steps), Some(starting_point)))
+                }
+
+                last_point = Some(starting_point);
                 starting_point = new_point;
+                steps += 1;
             } else {
                 let point = (starting_point.0, starting_point.1);
 
                 if self.structure[point] != true {
                     self.structure[point] = true;
-                    return true
+                    return Ok(())
                 } else {
-                    return false
+                    return Err((DropResult::Blocked, None))
                 }
             }
         }
 
-        false
+        Err((DropResult::Abyss, last_point))
+    }
+
+    /// Drops a single grain, returning whether it came to rest, could not
+    /// enter since the starting point is already filled, fell out, or was
+    /// still falling after `max_steps` steps.
+    pub fn drop_at(&mut self, starting_point: Sand, max_steps: usize) -> DropResult {
+        if self.intersects((starting_point.0, starting_point.1)) {
+            DropResult::Blocked
+        } else {
+            match self.drop_aux(starting_point, max_steps) {
+                Ok(()) => DropResult::Settled,
+                Err((result, _)) => result
+            }
+        }
+    }
+
+    pub fn drop_until_abyss(&mut self, starting_point: Sand) -> (usize, Option<Sand>) {
+        let mut count = 0;
+
+        loop {
+            match self.drop_aux(starting_point, usize::MAX) {
+                Ok(()) => count += 1,
+                Err((_, last_point)) => return (count, last_point)
+            }
+        }
     }
 
     fn drop_until_full(&mut self, starting_point: Sand) -> usize {
         let mut count = 0;
 
-        while self.drop_at(starting_point) {
+        while self.drop_at(starting_point, usize::MAX) == DropResult::Settled {
             count += 1;
         }
 
@@ -163,6 +225,45 @@ mod tests {
         assert_eq!(cave.drop_until_full(Sand(500, 0)), 24);
     }
 
+    #[test]
+    fn _01_drop_result() {
+        let mut cave = Cave::from_paths(Path::parse_all(Cursor::new(EXAMPLE)));
+
+        assert_eq!(cave.drop_at(Sand(500, 0), usize::MAX), DropResult::Settled);
+        assert_eq!(cave.drop_until_full(Sand(500, 0)), 23);
+        assert_eq!(cave.drop_at(Sand(500, 0), usize::MAX), DropResult::Abyss);
+    }
+
+    #[test]
+    fn _01_max_steps() {
+        let mut cave = Cave::from_paths(Path::parse_all(Cursor::new(EXAMPLE)));
+        let mut count = 0;
+
+        assert_eq!(cave.drop_at(Sand(500, 0), 3), DropResult::StalledAfter(3));
+        assert_eq!(cave.drop_at(Sand(500, 0), 8), DropResult::Settled);
+        while cave.drop_at(Sand(500, 0), 1000) == DropResult::Settled {
+            count += 1;
+        }
+        assert_eq!(count, 23);
+    }
+
+    #[test]
+    fn _01_drop_until_abyss() {
+        let mut cave = Cave::from_paths(Path::parse_all(Cursor::new(EXAMPLE)));
+        assert_eq!(cave.drop_until_abyss(Sand(500, 0)), (24, Some(Sand(493, 9))));
+    }
+
+    #[test]
+    fn _01_to_grid() {
+        let cave = Cave::from_paths(Path::parse_all(Cursor::new(EXAMPLE)));
+        let source = cave.to_grid((500, 0)).unwrap();
+
+        assert_eq!(cave.to_world(source), (500, 0));
+        assert_eq!(cave.to_grid((503, 9)), Some((503, 9)));
+        assert_eq!(cave.to_grid((504, 0)), None);
+        assert_eq!(cave.to_grid((500, 10)), None);
+    }
+
     #[test]
     fn _02_example() {
         let paths = Path::parse_all(Cursor::new(EXAMPLE));
@@ -178,5 +279,6 @@ mod tests {
         ].concat());
 
         assert_eq!(cave_with_floor.drop_until_full(Sand(500, 0)), 93);
+        assert_eq!(cave_with_floor.drop_at(Sand(500, 0), usize::MAX), DropResult::Blocked);
     }
 }
@@ -1,5 +1,5 @@
 use sscanf::sscanf;
-use std::{io::{prelude::*, stdin}, ops::{Bound, RangeBounds}};
+use std::{io::{prelude::*, stdin}, ops::{Bound, RangeBounds, RangeInclusive}};
 use btree_range_map::{RangeSet, AnyRange};
 
 struct Sensor {
@@ -35,9 +35,17 @@ impl Sensor {
     fn max_sensor_range(&self) -> i64 {
         self.distance_to(self.closest_beacon())
     }
+
+    /// Returns whether this sensor alone covers every `x` in `min_x..=max_x`
+    /// on the row `fixed_y`.
+    fn covers_row(&self, fixed_y: i64, min_x: i64, max_x: i64) -> bool {
+        let n = self.max_sensor_range() - (self.position.1 - fixed_y).abs();
+
+        n >= 0 && self.position.0 - n <= min_x && max_x <= self.position.0 + n
+    }
 }
 
-struct Sensors {
+pub struct Sensors {
     sensors: Vec<Sensor>
 }
 
@@ -46,6 +54,16 @@ impl Sensors {
         Self { sensors }
     }
 
+    /// Returns the `(min, max)` corners of the smallest box containing every
+    /// sensor and beacon.
+    pub fn auto_bounds(&self) -> ((i64, i64), (i64, i64)) {
+        self.sensors.iter()
+            .flat_map(|sensor| [sensor.position, sensor.closest_beacon()])
+            .fold(((i64::MAX, i64::MAX), (i64::MIN, i64::MIN)), |(min, max), (x, y)| {
+                ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+            })
+    }
+
     fn viable_ys(&self, min: i64, max: i64) -> impl Iterator<Item=i64> {
         let mut borders = vec! [];
 
@@ -79,6 +97,10 @@ impl Sensors {
         };
 
         for y in self.viable_ys(min.1, max.1) {
+            if self.sensors.iter().any(|sensor| sensor.covers_row(y, min.0, max.0)) {
+                continue; // no gap on this row
+            }
+
             let reachable = self.reachable_at_y(y);
 
             for gap in reachable.complement().iter().filter(|&gap| gap.intersects(&x_range)) {
@@ -111,11 +133,65 @@ impl Sensors {
         visited
     }
 
+    pub fn covered_intervals(&self, fixed_y: i64) -> Vec<(i64, i64)> {
+        self.reachable_at_y(fixed_y).iter()
+            .map(|range| {
+                let start = match range.start_bound() {
+                    Bound::Unbounded => i64::MIN,
+                    Bound::Excluded(&i) => i + 1,
+                    Bound::Included(&i) => i,
+                };
+                let end = match range.end_bound() {
+                    Bound::Unbounded => i64::MAX,
+                    Bound::Excluded(&i) => i - 1,
+                    Bound::Included(&i) => i,
+                };
+
+                (start, end)
+            })
+            .collect()
+    }
+
+    /// Returns the number of cells on row `y` within range of some sensor,
+    /// including any beacons on that row.
+    pub fn covered_count(&self, y: i64) -> usize {
+        self.reachable_at_y(y).len() as usize
+    }
+
+    /// Returns the total number of cells that cannot contain a beacon across
+    /// every row in `ys`.
+    pub fn no_beacon_count_rows(&self, ys: RangeInclusive<i64>) -> usize {
+        ys.map(|y| self.reachable_at_y_without_sensors(y).len() as usize).sum()
+    }
+
+    /// Returns the cells on row `fixed_y` within range of some sensor, except
+    /// for any beacons on that row. The set is rebuilt from inclusive ranges
+    /// since `RangeSet::remove` does nothing when removing an endpoint.
     fn reachable_at_y_without_sensors(&self, fixed_y: i64) -> RangeSet<i64> {
-        let mut visited = self.reachable_at_y(fixed_y);
+        let mut beacons = self.sensors.iter()
+            .map(|sensor| sensor.closest_beacon())
+            .filter(|beacon| beacon.1 == fixed_y)
+            .map(|beacon| beacon.0)
+            .collect::<Vec<_>>();
+        beacons.sort_unstable();
+        beacons.dedup();
+
+        let mut visited = RangeSet::new();
 
-        for sensor in self.sensors.iter().filter(|sensor| sensor.closest_beacon().1 == fixed_y) {
-            visited.remove(sensor.closest_beacon().0 as i64);
+        for (start, end) in self.covered_intervals(fixed_y) {
+            let mut next = start;
+
+            for &x in beacons.iter().filter(|&&x| start <= x && x <= end) {
+                if next < x {
+                    visited.insert(next..=(x - 1));
+                }
+
+                next = x + 1;
+            }
+
+            if next <= end {
+                visited.insert(next..=end);
+            }
         }
 
         visited
@@ -157,9 +233,55 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3"#;
         assert_eq!(sensors.reachable_at_y_without_sensors(10).len(), 26);
     }
 
+    #[test]
+    fn _01_covered_count() {
+        let sensors = Sensors::new(Sensor::parse_all(Cursor::new(EXAMPLE)));
+
+        assert_eq!(sensors.covered_count(10), 27);
+        assert_eq!(sensors.reachable_at_y_without_sensors(10).len(), 26);
+    }
+
+    #[test]
+    fn _01_no_beacon_count_rows() {
+        let sensors = Sensors::new(Sensor::parse_all(Cursor::new(EXAMPLE)));
+
+        assert_eq!(sensors.no_beacon_count_rows(10..=10), 26);
+        assert_eq!(sensors.no_beacon_count_rows(15..=15), 29);
+        assert_eq!(sensors.no_beacon_count_rows(0..=20), 599);
+    }
+
+    #[test]
+    fn _01_covered_intervals() {
+        let sensors = Sensors::new(Sensor::parse_all(Cursor::new(EXAMPLE)));
+        assert_eq!(sensors.covered_intervals(10), vec! [(-2, 24)]);
+        assert_eq!(sensors.covered_intervals(11), vec! [(-3, 13), (15, 25)]);
+    }
+
     #[test]
     fn _02_example() {
         let sensors = Sensors::new(Sensor::parse_all(Cursor::new(EXAMPLE)));
         assert_eq!(sensors.distress_beacon((0, 0), (20, 20)), (14, 11));
     }
+
+    #[test]
+    fn _02_auto_bounds() {
+        let sensors = Sensors::new(Sensor::parse_all(Cursor::new(EXAMPLE)));
+        let (min, max) = sensors.auto_bounds();
+
+        assert_eq!((min, max), ((-2, 0), (25, 22)));
+        assert!(min.0 <= 14 && 14 <= max.0 && min.1 <= 11 && 11 <= max.1);
+        assert_eq!(sensors.distress_beacon(min, max), (14, 11));
+    }
+
+    #[test]
+    fn _02_covers_row() {
+        let sensors = Sensors::new(Sensor::parse_all(Cursor::new(EXAMPLE)));
+        let sensor = &sensors.sensors[6];
+
+        assert!(sensor.covers_row(7, -1, 17));
+        assert!(!sensor.covers_row(7, -1, 18));
+        assert!(sensor.covers_row(16, 8, 8));
+        assert!(!sensor.covers_row(17, 8, 8));
+        assert!(!sensors.sensors.iter().any(|sensor| sensor.covers_row(11, 0, 20)));
+    }
 }
@@ -1,8 +1,8 @@
 use ndarray::prelude::*;
 use sscanf::sscanf;
-use std::{collections::VecDeque, io::{prelude::*, stdin}, fmt::Debug};
+use std::{collections::{HashMap, VecDeque}, io::{prelude::*, stdin}, fmt::Debug};
 
-struct Valve {
+pub struct Valve {
     name: String,
     flow_rate: u32,
     leads_to: Vec<String>,
@@ -28,15 +28,15 @@ impl Valve {
         }
     }
 
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         &self.name
     }
 
-    fn flow_rate(&self) -> u32 {
+    pub fn flow_rate(&self) -> u32 {
         self.flow_rate
     }
 
-    fn leads_to(&self) -> &[usize] {
+    pub fn leads_to(&self) -> &[usize] {
         &self.leads_to_indices
     }
 
@@ -61,11 +61,11 @@ struct Path {
 }
 
 impl Path {
-    fn starting_point(mins_remaining: u32) -> Self {
+    fn starting_point(at: usize, mins_remaining: u32) -> Self {
         Self {
             opened: 0,
             mins_remaining,
-            at: 0,
+            at,
             points: 0
         }
     }
@@ -93,18 +93,19 @@ impl Debug for Path {
     }
 }
 
-struct Valves {
+pub struct Valves {
     valves: Vec<Valve>
 }
 
 impl Valves {
-    fn parse_all<R: BufRead>(reader: R) -> Self {
-        let mut valves = reader.lines()
+    fn parse_valves<R: BufRead>(reader: R) -> Vec<Valve> {
+        reader.lines()
             .filter_map(|line| line.ok())
             .map(|line| Valve::parse(&line))
-            .collect::<Vec<_>>();
-        valves.sort_by_key(|valve| valve.name().to_string());
+            .collect::<Vec<_>>()
+    }
 
+    fn from_valves(valves: Vec<Valve>) -> Self {
         Self {
             valves: valves.iter()
                 .map(|valve| valve.with_valves(&valves))
@@ -112,6 +113,25 @@ impl Valves {
         }
     }
 
+    fn parse_all<R: BufRead>(reader: R) -> Self {
+        let mut valves = Self::parse_valves(reader);
+        valves.sort_by_key(|valve| valve.name().to_string());
+
+        Self::from_valves(valves)
+    }
+
+    pub fn parse_all_unsorted<R: BufRead>(reader: R) -> Self {
+        Self::from_valves(Self::parse_valves(reader))
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.valves.iter().position(|valve| valve.name() == name)
+    }
+
+    pub fn valve_by_name(&self, name: &str) -> Option<&Valve> {
+        self.index_of(name).map(|i| &self.valves[i])
+    }
+
     fn distance_matrix(&self) -> Array2<u32> {
         let n = self.valves.len();
         let mut shortest_so_far = Array2::from_elem((n, n), u32::MAX);
@@ -136,13 +156,28 @@ impl Valves {
         shortest_so_far
     }
 
-    fn max_flow_path_aux(
+    /// Returns whether every valve with a non-zero flow rate can be reached
+    /// from `AA`.
+    pub fn is_connected(&self) -> bool {
+        let distances = self.distance_matrix();
+
+        self.index_of("AA").is_some_and(|start| {
+            self.valves.iter()
+                .enumerate()
+                .filter(|(_, valve)| valve.flow_rate() > 0)
+                .all(|(i, _)| distances[(start, i)] != u32::MAX)
+        })
+    }
+
+    /// Visit every path, starting at `AA`, that opens some subset of the
+    /// non-zero valves not in `exclude` within `in_mins` minutes.
+    fn for_each_path(
         &self,
-        actors: usize,
         in_mins: u32,
         distances: &Array2<u32>,
-        exclude: u64
-    ) -> u32
+        exclude: u64,
+        mut visit: impl FnMut(&Path)
+    )
     {
         let nz_valves = self.valves.iter()
             .enumerate()
@@ -155,8 +190,7 @@ impl Valves {
             })
             .collect::<Vec<_>>();
         let mut to_visit = VecDeque::new();
-        let mut so_far = u32::MIN;
-        to_visit.push_back(Path::starting_point(in_mins));
+        to_visit.push_back(Path::starting_point(self.index_of("AA").unwrap(), in_mins));
 
         while let Some(path) = to_visit.pop_front() {
             let path = &path;
@@ -172,6 +206,21 @@ impl Valves {
                 ));
             }
 
+            visit(path);
+        }
+    }
+
+    fn max_flow_path_aux(
+        &self,
+        actors: usize,
+        in_mins: u32,
+        distances: &Array2<u32>,
+        exclude: u64
+    ) -> u32
+    {
+        let mut so_far = u32::MIN;
+
+        self.for_each_path(in_mins, distances, exclude, |path| {
             let points_with_actors = if actors > 1 {
                 path.points + self.max_flow_path_aux(
                     actors - 1,
@@ -186,16 +235,165 @@ impl Valves {
             if points_with_actors > so_far {
                 so_far = so_far.max(points_with_actors);
             }
-        }
+        });
 
         so_far
     }
 
+    /// Returns the most pressure that can be released within `in_mins` minutes
+    /// for every set of opened valves, keyed by the bitmask of valve indices.
+    pub fn best_per_subset(&self, in_mins: u32) -> HashMap<u64, u32> {
+        let distances = self.distance_matrix();
+        let mut best = HashMap::new();
+
+        self.for_each_path(in_mins, &distances, 0, |path| {
+            let points = best.entry(path.opened).or_insert(0);
+
+            *points = path.points.max(*points);
+        });
+
+        best
+    }
+
     fn max_flow_path(&self, actors: usize, in_mins: u32) -> u32 {
         let distances = self.distance_matrix();
 
         self.max_flow_path_aux(actors, in_mins, &distances, 0)
     }
+
+    /// Returns the pressure released by opening exactly the named valves, in
+    /// the best order, within `in_mins` minutes starting at `AA`. Returns
+    /// `None` if some name is unknown or the valves cannot all be opened in
+    /// time. Valves without any flow are never opened, so they are ignored.
+    pub fn can_open_all(&self, valves: &[&str], in_mins: u32) -> Option<u32> {
+        let to_open = valves.iter().try_fold(0u64, |so_far, name| {
+            let i = self.index_of(name)?;
+
+            Some(if self.valves[i].flow_rate() > 0 { so_far | (1 << i) } else { so_far })
+        })?;
+        let mut so_far = None;
+
+        self.for_each_path(in_mins, &self.distance_matrix(), !to_open, |path| {
+            if path.opened == to_open {
+                so_far = so_far.max(Some(path.points));
+            }
+        });
+
+        so_far
+    }
+
+    /// Returns the most pressure that can be released by opening every valve in
+    /// `to_open` starting at `at`, together with the `(valve, pressure)` each
+    /// opened valve contributes, in the order they are opened.
+    fn best_order(
+        &self,
+        distances: &Array2<u32>,
+        at: usize,
+        mins_remaining: u32,
+        to_open: u64
+    ) -> Option<(u32, Vec<(usize, u32)>)>
+    {
+        if to_open == 0 {
+            return Some((0, vec! []));
+        }
+
+        (0..self.valves.len())
+            .filter(|&i| to_open & (1 << i) != 0 && distances[(at, i)] < mins_remaining)
+            .filter_map(|i| {
+                let mins_remaining = mins_remaining - distances[(at, i)] - 1;
+                let contribution = self.valves[i].flow_rate() * mins_remaining;
+                let (points, mut order) = self.best_order(distances, i, mins_remaining, to_open & !(1 << i))?;
+                order.insert(0, (i, contribution));
+
+                Some((points + contribution, order))
+            })
+            .max_by_key(|(points, _)| *points)
+    }
+
+    /// Returns the pressure every valve opened along the best single actor
+    /// path contributes, i.e. its flow rate times the minutes it is open, in
+    /// the order the valves are opened.
+    pub fn valve_contributions(&self, in_mins: u32) -> Vec<(String, u32)> {
+        self.best_single_order(in_mins).into_iter()
+            .map(|(i, contribution)| (self.valves[i].name().to_string(), contribution))
+            .collect()
+    }
+
+    /// Returns the `(valve, pressure)` of every valve opened along the best
+    /// single actor path, in the order they are opened.
+    fn best_single_order(&self, in_mins: u32) -> Vec<(usize, u32)> {
+        let distances = self.distance_matrix();
+        let mut best: Option<Path> = None;
+
+        self.for_each_path(in_mins, &distances, 0, |path| {
+            if best.as_ref().map(|best| path.points > best.points).unwrap_or(true) {
+                best = Some(path.clone());
+            }
+        });
+
+        let best = best.unwrap();
+        let (_, order) = self.best_order(&distances, self.index_of("AA").unwrap(), in_mins, best.opened).unwrap();
+
+        order
+    }
+
+    /// Returns the total pressure released after each minute `1..=in_mins`
+    /// along the best single actor path. A valve that contributes `p` pressure
+    /// has been open for `p / flow_rate` minutes at the end.
+    pub fn pressure_timeline(&self, in_mins: u32) -> Vec<u32> {
+        let mut flow_from = vec! [0; in_mins as usize + 1];
+
+        for (i, contribution) in self.best_single_order(in_mins) {
+            let flow_rate = self.valves[i].flow_rate();
+            let opened_at = in_mins - contribution / flow_rate;
+
+            flow_from[opened_at as usize + 1] += flow_rate;
+        }
+
+        let mut flow_rate = 0;
+        let mut released = 0;
+
+        (1..=in_mins as usize)
+            .map(|minute| {
+                flow_rate += flow_from[minute];
+                released += flow_rate;
+                released
+            })
+            .collect()
+    }
+
+    /// Returns the maximum pressure that can be released for every time budget
+    /// from `0` to `max_mins`. With a single actor every path is only visited
+    /// once, since a path that finishes opening valves after `e` minutes
+    /// releases `flow_rate` less pressure for every minute cut from the budget.
+    pub fn max_flow_curve(&self, actors: usize, max_mins: u32) -> Vec<u32> {
+        let distances = self.distance_matrix();
+
+        if actors > 1 {
+            return (0..=max_mins)
+                .map(|in_mins| self.max_flow_path_aux(actors, in_mins, &distances, 0))
+                .collect();
+        }
+
+        let mut curve = vec! [0; max_mins as usize + 1];
+
+        self.for_each_path(max_mins, &distances, 0, |path| {
+            let flow_rate = self.valves.iter()
+                .enumerate()
+                .filter(|(i, _)| path.has_opened(*i))
+                .map(|(_, valve)| valve.flow_rate())
+                .sum::<u32>();
+            let elapsed = max_mins - path.mins_remaining;
+
+            for in_mins in elapsed..=max_mins {
+                let points = path.points - flow_rate * (max_mins - in_mins);
+
+                curve[in_mins as usize] = curve[in_mins as usize].max(points);
+            }
+        });
+
+        curve
+    }
 }
 
 fn main() {
@@ -233,4 +431,115 @@ Valve JJ has flow rate=21; tunnel leads to valve II"#;
         let valves = Valves::parse_all(Cursor::new(EXAMPLE));
         assert_eq!(valves.max_flow_path(2, 26), 1707);
     }
+
+    #[test]
+    fn _03_max_flow_curve() {
+        let valves = Valves::parse_all(Cursor::new(EXAMPLE));
+        let curve = valves.max_flow_curve(1, 30);
+
+        assert_eq!(curve.len(), 31);
+        assert_eq!(curve[30], 1651);
+        for (in_mins, &points) in curve.iter().enumerate() {
+            assert_eq!(points, valves.max_flow_path(1, in_mins as u32));
+        }
+        assert_eq!(valves.max_flow_curve(2, 26)[26], 1707);
+    }
+
+    #[test]
+    fn _03_best_per_subset() {
+        let valves = Valves::parse_all(Cursor::new(EXAMPLE));
+        let best = valves.best_per_subset(30);
+        let best_26 = valves.best_per_subset(26);
+        let two_actors = best_26.iter()
+            .flat_map(|(&mine, &my_points)| {
+                best_26.iter()
+                    .filter(move |(&theirs, _)| mine & theirs == 0)
+                    .map(move |(_, &their_points)| my_points + their_points)
+            })
+            .max();
+
+        assert_eq!(best.values().max(), Some(&1651));
+        assert_eq!(best[&0], 0);
+        assert_eq!(two_actors, Some(1707));
+
+        for (&opened, &points) in &best {
+            let names = (0..valves.valves.len())
+                .filter(|&i| opened & (1 << i) != 0)
+                .map(|i| valves.valves[i].name())
+                .collect::<Vec<_>>();
+
+            assert_eq!(valves.can_open_all(&names, 30), Some(points));
+        }
+    }
+
+    #[test]
+    fn _03_can_open_all() {
+        let valves = Valves::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(valves.can_open_all(&["DD", "BB"], 30), Some(885));
+        assert_eq!(valves.can_open_all(&["DD", "BB", "JJ", "HH", "EE", "CC"], 30), Some(1651));
+        assert_eq!(valves.can_open_all(&["HH"], 5), None);
+        assert_eq!(valves.can_open_all(&["ZZ"], 30), None);
+        assert_eq!(valves.can_open_all(&[], 30), Some(0));
+    }
+
+    #[test]
+    fn _03_valve_contributions() {
+        let valves = Valves::parse_all(Cursor::new(EXAMPLE));
+        let contributions = valves.valve_contributions(30);
+
+        assert_eq!(contributions.iter().map(|(_, pressure)| pressure).sum::<u32>(), 1651);
+        assert_eq!(contributions, vec! [
+            ("DD".to_string(), 560),
+            ("BB".to_string(), 325),
+            ("JJ".to_string(), 441),
+            ("HH".to_string(), 286),
+            ("EE".to_string(), 27),
+            ("CC".to_string(), 12),
+        ]);
+    }
+
+    #[test]
+    fn _03_pressure_timeline() {
+        let valves = Valves::parse_all(Cursor::new(EXAMPLE));
+        let timeline = valves.pressure_timeline(30);
+
+        assert_eq!(timeline.len(), 30);
+        assert_eq!(timeline[..6], [0, 0, 20, 40, 60, 93]);
+        assert_eq!(timeline.last(), Some(&1651));
+        assert!(timeline.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(valves.pressure_timeline(0), vec! []);
+    }
+
+    #[test]
+    fn _03_is_connected() {
+        let valves = Valves::parse_all(Cursor::new(EXAMPLE));
+        let isolated = Valves::parse_all(Cursor::new(format!("{}\nValve ZZ has flow rate=5; tunnel leads to valve ZZ", EXAMPLE)));
+
+        assert!(valves.is_connected());
+        assert!(!isolated.is_connected());
+        assert_eq!(isolated.max_flow_path(1, 30), 1651);
+    }
+
+    #[test]
+    fn _03_valve_by_name() {
+        let valves = Valves::parse_all(Cursor::new(EXAMPLE));
+        let dd = valves.valve_by_name("DD").unwrap();
+
+        assert_eq!(dd.flow_rate(), 20);
+        assert_eq!(dd.leads_to().iter().map(|&i| valves.valves[i].name()).collect::<Vec<_>>(), vec! ["CC", "AA", "EE"]);
+        assert!(valves.valve_by_name("ZZ").is_none());
+    }
+
+    #[test]
+    fn _03_unsorted() {
+        let reversed = EXAMPLE.lines().rev().collect::<Vec<_>>().join("\n");
+        let valves = Valves::parse_all_unsorted(Cursor::new(reversed));
+
+        assert_eq!(valves.index_of("AA"), Some(9));
+        assert_eq!(valves.index_of("JJ"), Some(0));
+        assert_eq!(valves.index_of("ZZ"), None);
+        assert_eq!(valves.max_flow_path(1, 30), 1651);
+        assert_eq!(valves.max_flow_path(2, 26), 1707);
+    }
 }
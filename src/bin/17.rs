@@ -1,8 +1,24 @@
-use ndarray::{Array, Array2, s, stack, Axis, concatenate, ArrayView2};
+use ndarray::{Array, Array2, s, Axis, concatenate, ArrayView2};
 use std::{io::{stdin, BufRead}, collections::HashMap};
 
+const CHAMBER_WIDTH: usize = 7;
+
 trait Shape {
-    fn starting_point(&self) -> Array2<i8>;
+    fn starting_point(&self, width: usize) -> Array2<i8>;
+}
+
+/// Place the given rows two units away from the left wall of a chamber that
+/// is `width` units wide.
+fn pad_rows(rows: &[&[i8]], width: usize) -> Array2<i8> {
+    let mut rock = Array2::from_elem((rows.len(), width), 0i8);
+
+    for (i, row) in rows.iter().enumerate() {
+        assert!(2 + row.len() <= width, "chamber is too narrow for rock");
+
+        rock.slice_mut(s! [i, 2..(2 + row.len())]).assign(&Array::from_vec(row.to_vec()));
+    }
+
+    rock
 }
 
 struct Line;
@@ -13,10 +29,10 @@ struct O;
 
 /// `####`
 impl Shape for Line {
-    fn starting_point(&self) -> Array2<i8> {
-        stack(Axis(0), &[
-            Array::from_vec(vec! [0, 0, 1, 1, 1, 1, 0]).view(),
-        ]).unwrap()
+    fn starting_point(&self, width: usize) -> Array2<i8> {
+        pad_rows(&[
+            &[1, 1, 1, 1],
+        ], width)
     }
 }
 
@@ -26,12 +42,12 @@ impl Shape for Line {
 /// .#.
 /// ```
 impl Shape for Plus {
-    fn starting_point(&self) -> Array2<i8> {
-        stack(Axis(0), &[
-            Array::from_vec(vec! [0, 0, 0, 1, 0, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 1, 1, 1, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 0, 1, 0, 0, 0]).view(),
-        ]).unwrap()
+    fn starting_point(&self, width: usize) -> Array2<i8> {
+        pad_rows(&[
+            &[0, 1, 0],
+            &[1, 1, 1],
+            &[0, 1, 0],
+        ], width)
     }
 }
 
@@ -41,12 +57,12 @@ impl Shape for Plus {
 /// ###
 /// ```
 impl Shape for L {
-    fn starting_point(&self) -> Array2<i8> {
-        stack(Axis(0), &[
-            Array::from_vec(vec! [0, 0, 1, 1, 1, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 0, 0, 1, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 0, 0, 1, 0, 0]).view(),
-        ]).unwrap()
+    fn starting_point(&self, width: usize) -> Array2<i8> {
+        pad_rows(&[
+            &[1, 1, 1],
+            &[0, 0, 1],
+            &[0, 0, 1],
+        ], width)
     }
 }
 
@@ -57,13 +73,13 @@ impl Shape for L {
 /// #
 /// ```
 impl Shape for I {
-    fn starting_point(&self) -> Array2<i8> {
-        stack(Axis(0), &[
-            Array::from_vec(vec! [0, 0, 1, 0, 0, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 1, 0, 0, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 1, 0, 0, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 1, 0, 0, 0, 0]).view(),
-        ]).unwrap()
+    fn starting_point(&self, width: usize) -> Array2<i8> {
+        pad_rows(&[
+            &[1],
+            &[1],
+            &[1],
+            &[1],
+        ], width)
     }
 }
 
@@ -72,11 +88,11 @@ impl Shape for I {
 /// ##
 /// ```
 impl Shape for O {
-    fn starting_point(&self) -> Array2<i8> {
-        stack(Axis(0), &[
-            Array::from_vec(vec! [0, 0, 1, 1, 0, 0, 0]).view(),
-            Array::from_vec(vec! [0, 0, 1, 1, 0, 0, 0]).view(),
-        ]).unwrap()
+    fn starting_point(&self, width: usize) -> Array2<i8> {
+        pad_rows(&[
+            &[1, 1],
+            &[1, 1],
+        ], width)
     }
 }
 
@@ -102,12 +118,14 @@ fn try_push_left(rock: &Array2<i8>) -> Array2<i8>{
 }
 
 fn try_push_right(rock: &Array2<i8>) -> Array2<i8>{
-    if rock.slice(s! [.., 6]).sum() > 0 {
+    let last = rock.dim().1 - 1;
+
+    if rock.slice(s! [.., last]).sum() > 0 {
         rock.clone()
     } else {
         concatenate(Axis(1), &[
             Array::from_elem((rock.dim().0, 1), 0i8).view(),
-            rock.slice(s! [.., ..6]),
+            rock.slice(s! [.., ..last]),
         ]).unwrap()
     }
 }
@@ -159,7 +177,7 @@ fn fall_rock (
     if chamber.dim().0 < (y + rock.dim().0) {
         chamber = concatenate(Axis(0), &[
             chamber.view(),
-            Array::from_elem((y + rock.dim().0 - chamber.dim().0, 7), 0i8).view(),
+            Array::from_elem((y + rock.dim().0 - chamber.dim().0, chamber.dim().1), 0i8).view(),
         ]).unwrap();
     }
 
@@ -172,6 +190,18 @@ fn fall_rock (
     (chamber, steps)
 }
 
+/// Returns the index of the topmost filled row in each column of the chamber,
+/// where row `0` is the floor.
+pub fn column_heights(chamber: ArrayView2<i8>) -> [usize; CHAMBER_WIDTH] {
+    let mut heights = [0; CHAMBER_WIDTH];
+
+    for (x, column) in chamber.lanes(Axis(0)).into_iter().enumerate().take(CHAMBER_WIDTH) {
+        heights[x] = column.iter().rposition(|&s| s > 0).unwrap_or(0);
+    }
+
+    heights
+}
+
 // num_rounds: usize
 fn play_aux<T>(
     mut until_fn: impl FnMut(ArrayView2<i8>, usize, usize, usize) -> Option<T>,
@@ -181,7 +211,7 @@ fn play_aux<T>(
     mut jet_stream_index: usize
 ) -> Option<T>
 {
-    let mut chamber = starting_chamber.unwrap_or(Array2::from_elem((1, 7), 1i8));
+    let mut chamber = starting_chamber.unwrap_or(Array2::from_elem((1, CHAMBER_WIDTH), 1i8));
     let mut steps = 0;
 
     for (rock_i, rock) in rocks().iter().enumerate().cycle().skip(rock_index) {
@@ -189,9 +219,10 @@ fn play_aux<T>(
             return Some(x)
         }
 
+        let width = chamber.dim().1;
         let (next_chamber, jet_stream_steps) = fall_rock(
             chamber,
-            rock.starting_point(),
+            rock.starting_point(width),
             &mut jet_stream_seq.iter().cloned().cycle().skip(jet_stream_index)
         );
 
@@ -205,7 +236,15 @@ fn play_aux<T>(
     None
 }
 
-fn play(num_rounds: usize, jet_stream_seq: &[char]) -> usize {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CycleInfo {
+    pub start_step: usize,
+    pub start_height: usize,
+    pub length: usize,
+    pub height_per_cycle: usize,
+}
+
+fn detect_cycle(num_rounds: usize, jet_stream_seq: &[char], width: usize) -> Option<(Array2<i8>, usize, usize, CycleInfo)> {
     // when playing with large `num_rounds` it the play ground should eventually
     // look like this:
     //
@@ -221,7 +260,8 @@ fn play(num_rounds: usize, jet_stream_seq: &[char]) -> usize {
     // and end garbage looks like.
     //
     let mut visited = HashMap::new();
-    let (after_cycle, jet_stream_cycle_at, rocks_cycle_at, cycle_step_length, cycle_height, start_garbage_height, start_garbage_steps) = play_aux(move |chamber, i, rock_index, jet_stream_seq| {
+
+    play_aux(move |chamber, i, rock_index, jet_stream_seq| {
         if i >= num_rounds {
             Some(None)
         } else if chamber.dim().0 >= 10 {
@@ -233,12 +273,14 @@ fn play(num_rounds: usize, jet_stream_seq: &[char]) -> usize {
             if visited.contains_key(&(rock_index, jet_stream_seq, contour.to_owned())) {
                 let (cycle_start_step, cycle_start_height) = visited[&(rock_index, jet_stream_seq, contour.to_owned())];
                 let (cycle_end_step, cycle_end_height) = (i, chamber.dim().0);
-                let cycle_step_length = cycle_end_step - cycle_start_step;
-                let cycle_height = cycle_end_height - cycle_start_height;
-                let start_garbage_height = cycle_start_height;
-                let start_garbage_steps = cycle_start_step;
-
-                Some(Some((chamber.to_owned(), jet_stream_seq, rock_index, cycle_step_length, cycle_height, start_garbage_height, start_garbage_steps)))
+                let cycle_info = CycleInfo {
+                    start_step: cycle_start_step,
+                    start_height: cycle_start_height - 1,
+                    length: cycle_end_step - cycle_start_step,
+                    height_per_cycle: cycle_end_height - cycle_start_height,
+                };
+
+                Some(Some((chamber.to_owned(), jet_stream_seq, rock_index, cycle_info)))
             } else {
                 visited.insert((rock_index, jet_stream_seq, contour.to_owned()), (i, chamber.dim().0));
                 None
@@ -246,11 +288,70 @@ fn play(num_rounds: usize, jet_stream_seq: &[char]) -> usize {
         } else {
             None
         }
-    }, None, 0, jet_stream_seq, 0).unwrap().unwrap();
+    }, Some(Array2::from_elem((1, width), 1i8)), 0, jet_stream_seq, 0).unwrap()
+}
+
+pub fn find_cycle(jet_stream_seq: &[char]) -> CycleInfo {
+    let (_, _, _, cycle_info) = detect_cycle(usize::MAX, jet_stream_seq, CHAMBER_WIDTH).unwrap();
+
+    cycle_info
+}
+
+/// Returns the smallest number of rocks that must fall before the tower is at
+/// least `target_height` tall. Heights within a cycle repeat, offset by
+/// `height_per_cycle`, so only the first cycle has to be simulated.
+pub fn rocks_to_reach(target_height: usize, jet_stream_seq: &[char]) -> usize {
+    let cycle_info = find_cycle(jet_stream_seq);
+    let mut heights = vec! [];
+
+    play_aux(|chamber, i, _, _| {
+        heights.push(chamber.dim().0 - 1);
+
+        if i >= cycle_info.start_step + cycle_info.length {
+            Some(())
+        } else {
+            None
+        }
+    }, None, 0, jet_stream_seq, 0).unwrap();
+
+    if target_height <= cycle_info.start_height {
+        return heights.iter().position(|&height| height >= target_height).unwrap();
+    }
+
+    let remaining = target_height - cycle_info.start_height;
+    let num_cycles = (remaining - 1) / cycle_info.height_per_cycle;
+    let remaining_in_cycle = remaining - num_cycles * cycle_info.height_per_cycle;
+    let steps_in_cycle = heights[cycle_info.start_step..].iter()
+        .position(|&height| height - cycle_info.start_height >= remaining_in_cycle)
+        .unwrap();
+
+    cycle_info.start_step + num_cycles * cycle_info.length + steps_in_cycle
+}
+
+/// Drops rocks until the tower, not counting the floor, is at least `target`
+/// rows tall. Returns the number of dropped rocks and the resulting chamber.
+pub fn play_until_height(target: usize, jet_stream_seq: &[char]) -> (usize, Array2<i8>) {
+    play_aux(|chamber, i, _, _| {
+        let height = chamber.dim().0 - 1;
+
+        if height >= target {
+            Some((i, chamber.to_owned()))
+        } else {
+            None
+        }
+    }, None, 0, jet_stream_seq, 0).unwrap()
+}
+
+fn play(num_rounds: usize, jet_stream_seq: &[char]) -> usize {
+    play_width(num_rounds, jet_stream_seq, CHAMBER_WIDTH)
+}
+
+fn play_width(num_rounds: usize, jet_stream_seq: &[char], width: usize) -> usize {
+    let (after_cycle, jet_stream_cycle_at, rocks_cycle_at, cycle_info) = detect_cycle(num_rounds, jet_stream_seq, width).unwrap();
 
     // figure out how many garbage lines we have at the end of the cycles
-    let num_cycles = (num_rounds - start_garbage_steps) / cycle_step_length;
-    let end_garbage_steps = num_rounds - start_garbage_steps - num_cycles * cycle_step_length;
+    let num_cycles = (num_rounds - cycle_info.start_step) / cycle_info.length;
+    let end_garbage_steps = num_rounds - cycle_info.start_step - num_cycles * cycle_info.length;
     let after_cycle_garbage = play_aux(|chamber, i, _, _| {
         if i >= end_garbage_steps {
             Some(chamber.to_owned())
@@ -260,10 +361,9 @@ fn play(num_rounds: usize, jet_stream_seq: &[char]) -> usize {
     }, Some(after_cycle.to_owned()), rocks_cycle_at, jet_stream_seq, jet_stream_cycle_at).unwrap();
     let end_garbage_height = after_cycle_garbage.dim().0 - after_cycle.dim().0;
 
-    start_garbage_height
-        + num_cycles * cycle_height
+    cycle_info.start_height
+        + num_cycles * cycle_info.height_per_cycle
         + end_garbage_height
-        - 1
 }
 
 fn main() {
@@ -277,9 +377,21 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ndarray::stack;
 
     const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
 
+    /// Returns the height of the tower after `n` rocks have fallen.
+    fn height_after(jets: &[char], n: usize) -> usize {
+        play_aux(|chamber, i, _, _| {
+            if i >= n {
+                Some(chamber.dim().0 - 1)
+            } else {
+                None
+            }
+        }, None, 0, jets, 0).unwrap()
+    }
+
     #[test]
     fn _01_falling_rocks() {
         let sequence = EXAMPLE.chars().collect::<Vec<_>>();
@@ -313,6 +425,21 @@ mod tests {
         ]).unwrap());
     }
 
+    #[test]
+    fn _01_column_heights() {
+        let sequence = EXAMPLE.chars().collect::<Vec<_>>();
+        let heights = play_aux(|chamber, i, _, _| {
+            if i >= 10 {
+                Some(column_heights(chamber))
+            } else {
+                None
+            }
+        }, None, 0, &sequence, 0).unwrap();
+
+        assert_eq!(heights, [14, 14, 13, 13, 17, 15, 0]);
+        assert_eq!(column_heights(Array2::from_elem((1, CHAMBER_WIDTH), 1i8).view()), [0; CHAMBER_WIDTH]);
+    }
+
     #[test]
     fn _01_example() {
         let sequence = EXAMPLE.chars().collect::<Vec<_>>();
@@ -324,4 +451,61 @@ mod tests {
         let sequence = EXAMPLE.chars().collect::<Vec<_>>();
         assert_eq!(play(1000000000000, &sequence), 1514285714288);
     }
+
+    #[test]
+    fn _01_wide_chamber() {
+        let sequence = EXAMPLE.chars().collect::<Vec<_>>();
+
+        assert_eq!(Line {}.starting_point(9), stack(Axis(0), &[
+            Array::from_vec(vec! [0, 0, 1, 1, 1, 1, 0, 0, 0]).view(),
+        ]).unwrap());
+        assert_eq!(play_width(2022, &sequence, 7), 3068);
+        assert_eq!(play_width(1000000000000, &sequence, 7), 1514285714288);
+        assert!(play_width(2022, &sequence, 9) < 3068);
+    }
+
+    #[test]
+    fn _02_rocks_to_reach() {
+        let sequence = EXAMPLE.chars().collect::<Vec<_>>();
+
+        assert_eq!(rocks_to_reach(0, &sequence), 0);
+        assert_eq!(rocks_to_reach(1, &sequence), 1);
+
+        for target_height in [3068, 1514285714288] {
+            let rocks = rocks_to_reach(target_height, &sequence);
+
+            assert!(play(rocks, &sequence) >= target_height);
+            assert!(play(rocks - 1, &sequence) < target_height);
+        }
+
+        assert!(rocks_to_reach(3068, &sequence) <= 2022);
+        assert!(rocks_to_reach(1514285714288, &sequence) <= 1000000000000);
+    }
+
+    #[test]
+    fn _02_play_until_height() {
+        let sequence = EXAMPLE.chars().collect::<Vec<_>>();
+
+        for target in [0, 1, 17, 100, 3068] {
+            let (rocks, chamber) = play_until_height(target, &sequence);
+
+            assert_eq!(chamber.dim().0 - 1, height_after(&sequence, rocks));
+            assert!(height_after(&sequence, rocks) >= target);
+            assert_eq!(rocks, rocks_to_reach(target, &sequence));
+            if rocks > 0 {
+                assert!(height_after(&sequence, rocks - 1) < target);
+            }
+        }
+    }
+
+    #[test]
+    fn _02_find_cycle() {
+        let sequence = EXAMPLE.chars().collect::<Vec<_>>();
+        let cycle_info = find_cycle(&sequence);
+
+        assert!(cycle_info.length > 0);
+        assert_eq!(height_after(&sequence, cycle_info.start_step), cycle_info.start_height);
+        assert_eq!(height_after(&sequence, cycle_info.start_step + cycle_info.length), cycle_info.start_height + cycle_info.height_per_cycle);
+        assert_eq!(height_after(&sequence, cycle_info.start_step + 2 * cycle_info.length), cycle_info.start_height + 2 * cycle_info.height_per_cycle);
+    }
 }
\ No newline at end of file
@@ -1,88 +1,84 @@
 use sscanf::sscanf;
-use std::{collections::{HashSet, BinaryHeap}, io::{BufRead, stdin}};
+use std::{collections::HashSet, fmt::Display, io::{BufRead, stdin}};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z
+}
 
 #[derive(Clone, PartialEq, Eq, Hash)]
-struct Voxel {
+pub struct Voxel {
     x: i16,
     y: i16,
     z: i16
 }
 
-impl Voxel {
-    fn parse(line: &str) -> Self {
-        let (x, y, z) = sscanf!(line, "{},{},{}", i16, i16, i16).unwrap();
-
-        Self { x, y, z }
-    }
-
-    fn zero() -> Self {
-        Self {
-            x: 0,
-            y: 0,
-            z: 0,
-        }
-    }
-
-    fn distance_to(&self, other: &Self) -> i16 {
-        (self.x - other.x).abs()
-            + (self.y - other.y).abs()
-            + (self.z - other.z).abs()
-    }
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    line: String
+}
 
-    fn sides(&self) -> impl Iterator<Item=Voxel> {
-        [
-            Voxel { x: self.x - 1, ..*self },
-            Voxel { x: self.x + 1, ..*self },
-            Voxel { y: self.y - 1, ..*self },
-            Voxel { y: self.y + 1, ..*self },
-            Voxel { z: self.z - 1, ..*self },
-            Voxel { z: self.z + 1, ..*self },
-        ].into_iter()
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "could not parse voxel -- {:?}", self.line)
     }
 }
 
-struct VoxelDistance(Voxel, i16);
-
-impl VoxelDistance {
-    fn new(voxel: Voxel, distance: i16) -> Self {
-        Self(voxel, distance)
+/// Voxels are ordered by `z`, then `y`, then `x`.
+impl Ord for Voxel {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.z, self.y, self.x).cmp(&(other.z, other.y, other.x))
     }
 }
 
-impl Eq for VoxelDistance {
-    // pass
+impl PartialOrd for Voxel {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl PartialEq for VoxelDistance {
-    fn eq(&self, other: &Self) -> bool {
-        self.1.eq(&other.1)
+impl Voxel {
+    fn parse(line: &str) -> Result<Self, ParseError> {
+        match sscanf!(line, "{},{},{}", i16, i16, i16) {
+            Ok((x, y, z)) => Ok(Self { x, y, z }),
+            Err(_) => Err(ParseError { line: line.to_string() })
+        }
     }
-}
 
-impl Ord for VoxelDistance {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.1.cmp(&self.1)
+    fn faces(&self) -> impl Iterator<Item=(Voxel, Axis, bool)> {
+        [
+            (Voxel { x: self.x - 1, ..*self }, Axis::X, false),
+            (Voxel { x: self.x + 1, ..*self }, Axis::X, true),
+            (Voxel { y: self.y - 1, ..*self }, Axis::Y, false),
+            (Voxel { y: self.y + 1, ..*self }, Axis::Y, true),
+            (Voxel { z: self.z - 1, ..*self }, Axis::Z, false),
+            (Voxel { z: self.z + 1, ..*self }, Axis::Z, true),
+        ].into_iter()
     }
-}
 
-impl PartialOrd for VoxelDistance {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    fn sides(&self) -> impl Iterator<Item=Voxel> {
+        self.faces().map(|(voxel, _, _)| voxel)
     }
 }
 
-struct Voxels {
+pub struct Voxels {
     voxels: HashSet<Voxel>
 }
 
 impl Voxels {
     fn parse_all(reader: impl BufRead) -> Self {
+        Self::try_parse_all(reader).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_parse_all(reader: impl BufRead) -> Result<Self, ParseError> {
         let voxels = reader.lines()
             .filter_map(|line| line.ok())
             .map(|line| Voxel::parse(&line))
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        Self { voxels }
+        Ok(Self { voxels })
     }
 
     fn sides<'a>(&'a self) -> impl Iterator<Item=Voxel> + 'a {
@@ -91,31 +87,57 @@ impl Voxels {
             .filter(|voxel| !self.voxels.contains(&voxel))
     }
 
-    fn is_reachable(&self, starting_point: &Voxel, end_point: &Voxel) -> bool {
-        let mut visited = HashSet::new();
-        let mut to_visit = BinaryHeap::new();
-        to_visit.push(VoxelDistance::new(
-            starting_point.clone(),
-            starting_point.distance_to(&end_point)
-        ));
+    pub fn sorted(&self) -> Vec<Voxel> {
+        let mut voxels = self.voxels.iter().cloned().collect::<Vec<_>>();
+        voxels.sort();
+        voxels
+    }
 
-        while let Some(VoxelDistance(curr, _)) = to_visit.pop() {
-            if end_point.eq(&curr) {
-                return true;
-            }
+    pub fn exposed_faces(&self) -> Vec<(Voxel, Axis, bool)> {
+        self.voxels.iter()
+            .flat_map(|voxel| {
+                voxel.faces()
+                    .filter(|(side, _, _)| !self.voxels.contains(side))
+                    .map(|(_, axis, positive)| (voxel.clone(), axis, positive))
+            })
+            .collect()
+    }
 
+    pub fn shared_faces(&self) -> usize {
+        self.voxels.iter()
+            .flat_map(|voxel| voxel.faces())
+            .filter(|(side, _, positive)| *positive && self.voxels.contains(side))
+            .count()
+    }
+
+    /// Returns the total surface area and the exterior surface area, the
+    /// latter found by flood filling the air around the droplet within its
+    /// bounding box padded by one.
+    pub fn surface_areas(&self) -> (usize, usize) {
+        let lower = |f: fn(&Voxel) -> i16| self.voxels.iter().map(f).min().unwrap_or(0) - 1;
+        let upper = |f: fn(&Voxel) -> i16| self.voxels.iter().map(f).max().unwrap_or(0) + 1;
+        let min = Voxel { x: lower(|v| v.x), y: lower(|v| v.y), z: lower(|v| v.z) };
+        let max = Voxel { x: upper(|v| v.x), y: upper(|v| v.y), z: upper(|v| v.z) };
+        let within = |v: &Voxel| {
+            (min.x..=max.x).contains(&v.x)
+                && (min.y..=max.y).contains(&v.y)
+                && (min.z..=max.z).contains(&v.z)
+        };
+
+        let mut outside = HashSet::from([min.clone()]);
+        let mut to_visit = vec![min.clone()];
+
+        while let Some(curr) = to_visit.pop() {
             for next_voxel in curr.sides() {
-                if !self.voxels.contains(&next_voxel) && !visited.contains(&next_voxel) {
-                    visited.insert(next_voxel.clone());
-                    to_visit.push(VoxelDistance::new(
-                        next_voxel.clone(),
-                        next_voxel.distance_to(&end_point),
-                    ));
+                if within(&next_voxel) && !self.voxels.contains(&next_voxel) && outside.insert(next_voxel.clone()) {
+                    to_visit.push(next_voxel);
                 }
             }
         }
 
-        false
+        self.sides().fold((0, 0), |(total, exterior), side| {
+            (total + 1, exterior + outside.contains(&side) as usize)
+        })
     }
 
     #[cfg(test)]
@@ -128,8 +150,10 @@ fn main() {
     let stdin = stdin().lock();
     let voxels = Voxels::parse_all(stdin);
 
-    println!("{}", voxels.sides().count());
-    println!("{}", voxels.sides().filter(|side| voxels.is_reachable(&side, &Voxel::zero())).count());
+    let (total, exterior) = voxels.surface_areas();
+
+    println!("{}", total);
+    println!("{}", exterior);
 }
 
 #[cfg(test)]
@@ -159,10 +183,60 @@ mod tests {
         assert_eq!(voxels.sides().count(), 64);
     }
 
+    #[test]
+    fn _01_exposed_faces() {
+        let voxels = Voxels::parse_all(Cursor::new(EXAMPLE));
+        let faces = voxels.exposed_faces();
+
+        assert_eq!(faces.len(), voxels.sides().count());
+        assert!(faces.contains(&(Voxel::parse("2,2,6").unwrap(), Axis::Z, true)));
+        assert!(!faces.contains(&(Voxel::parse("2,2,1").unwrap(), Axis::Z, true)));
+    }
+
+    #[test]
+    fn _01_parse_error() {
+        let with_comment = format!("{}\n# stray comment\n1,1,1", EXAMPLE);
+        let err = Voxels::try_parse_all(Cursor::new(with_comment)).err().unwrap();
+
+        assert_eq!(err, ParseError { line: "# stray comment".to_string() });
+        assert_eq!(err.to_string(), "could not parse voxel -- \"# stray comment\"");
+        assert!(Voxel::parse("").is_err());
+        assert_eq!(Voxels::try_parse_all(Cursor::new(EXAMPLE)).map(|voxels| voxels.len()), Ok(13));
+    }
+
+    #[test]
+    fn _01_shared_faces() {
+        let voxels = Voxels::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(voxels.shared_faces(), 7);
+        assert_eq!(6 * voxels.len() - 2 * voxels.shared_faces(), voxels.sides().count());
+    }
+
+    #[test]
+    fn _01_sorted() {
+        let voxels = Voxels::parse_all(Cursor::new(EXAMPLE));
+        let sorted = voxels.sorted();
+        let coords = |voxel: &Voxel| (voxel.x, voxel.y, voxel.z);
+
+        assert_eq!(sorted.len(), 13);
+        assert_eq!(coords(&sorted[0]), (2, 2, 1));
+        assert_eq!(coords(&sorted[1]), (2, 1, 2));
+        assert_eq!(coords(&sorted[12]), (2, 2, 6));
+        assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
     #[test]
     fn _02_example() {
         let voxels = Voxels::parse_all(Cursor::new(EXAMPLE));
 
-        assert_eq!(voxels.sides().filter(|side| voxels.is_reachable(&side, &Voxel::zero())).count(), 58);
+        assert_eq!(voxels.surface_areas().1, 58);
+    }
+
+    #[test]
+    fn _02_surface_areas() {
+        let voxels = Voxels::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(voxels.surface_areas(), (64, 58));
+        assert_eq!(Voxels::parse_all(Cursor::new("1,1,1")).surface_areas(), (6, 6));
     }
 }
\ No newline at end of file
@@ -143,6 +143,42 @@ impl Blueprint {
     fn max(&self) -> &Resources {
         &self.max
     }
+
+    /// Returns whether a geode robot could possibly be built early enough to
+    /// crack at least one geode within `remaining_time` minutes. This relaxes
+    /// the real rules by building one of every affordable robot each minute
+    /// without spending any resources, so a blueprint that is not viable can
+    /// never produce a geode, whereas a viable one still might not.
+    fn is_viable(&self, remaining_time: usize) -> bool {
+        let mut resources = Resources::new(0, 0, 0, 0);
+        let mut robots = Resources::new(1, 0, 0, 0);
+
+        for _ in 1..remaining_time {
+            let affordable = |robot: Robot| {
+                let cost = self.cost(robot);
+
+                cost.ore() <= resources.ore()
+                    && cost.clay() <= resources.clay()
+                    && cost.obsidian() <= resources.obsidian()
+            };
+
+            if affordable(Robot::Geode) {
+                return true;
+            }
+
+            let built = Resources::new(
+                affordable(Robot::Ore) as u16,
+                affordable(Robot::Clay) as u16,
+                affordable(Robot::Obsidian) as u16,
+                0,
+            );
+
+            resources += robots;
+            robots += built;
+        }
+
+        false
+    }
 }
 
 #[derive(Debug)]
@@ -239,12 +275,29 @@ impl<'a> Factory<'a> {
 }
 
 fn largest_geode_count(blueprint: &Blueprint, remaining_time: usize) -> usize {
+    largest_geode_count_limited(blueprint, remaining_time, usize::MAX).0
+}
+
+/// Returns the largest geode count found within `max_nodes` expanded states,
+/// and whether the search finished before running out of expansions.
+fn largest_geode_count_limited(blueprint: &Blueprint, remaining_time: usize, max_nodes: usize) -> (usize, bool) {
+    if !blueprint.is_viable(remaining_time) {
+        return (0, true);
+    }
+
     let mut so_far = usize::MIN;
+    let mut num_nodes = 0;
     let mut to_visit = Vec::new();
     to_visit.push(Factory::new(&blueprint, remaining_time));
 
-    while let Some(state) = to_visit.pop() {
+    while num_nodes < max_nodes {
+        let state = match to_visit.pop() {
+            Some(state) => state,
+            None => break
+        };
+
         so_far = so_far.max(state.score());
+        num_nodes += 1;
 
         for plan in state.plans() {
             let next_state = state.next_step(&plan);
@@ -255,7 +308,7 @@ fn largest_geode_count(blueprint: &Blueprint, remaining_time: usize) -> usize {
         }
     }
 
-    so_far
+    (so_far, to_visit.is_empty())
 }
 
 struct Blueprints {
@@ -291,12 +344,20 @@ impl Blueprints {
     }
 }
 
+fn solve(blueprints: &Blueprints, time1: usize, time2: usize, take2: usize) -> (usize, usize) {
+    (
+        blueprints.total_quality_level(time1),
+        blueprints.take(take2).geode_product(time2),
+    )
+}
+
 fn main() {
     let stdin = stdin().lock();
     let blueprints = Blueprints::parse_all(stdin);
+    let (total_quality_level, geode_product) = solve(&blueprints, 24, 32, 3);
 
-    println!("{}", blueprints.total_quality_level(24));
-    println!("{}", blueprints.take(3).geode_product(32));
+    println!("{}", total_quality_level);
+    println!("{}", geode_product);
 }
 
 #[cfg(test)]
@@ -318,4 +379,37 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         let blueprints = Blueprints::parse_all(Cursor::new(EXAMPLE));
         assert_eq!(blueprints.take(3).geode_product(32), 3472);
     }
+
+    #[test]
+    fn _03_is_viable() {
+        let blueprints = Blueprints::parse_all(Cursor::new(EXAMPLE));
+        let degenerate = Blueprint::parse("Blueprint 3: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 200 clay. Each geode robot costs 2 ore and 7 obsidian.");
+
+        assert!(blueprints.blueprints.iter().all(|blueprint| blueprint.is_viable(24)));
+        assert!(!blueprints.blueprints.iter().any(|blueprint| blueprint.is_viable(5)));
+        assert!(!degenerate.is_viable(24));
+        assert_eq!(largest_geode_count(&degenerate, 24), 0);
+    }
+
+    #[test]
+    fn _03_largest_geode_count_limited() {
+        let blueprints = Blueprints::parse_all(Cursor::new(EXAMPLE));
+
+        for blueprint in &blueprints.blueprints {
+            let (partial, completed) = largest_geode_count_limited(blueprint, 24, 10);
+
+            assert_eq!(largest_geode_count_limited(blueprint, 24, usize::MAX), (largest_geode_count(blueprint, 24), true));
+            assert!(!completed);
+            assert!(partial <= largest_geode_count(blueprint, 24));
+        }
+
+        assert_eq!(largest_geode_count_limited(&blueprints.blueprints[0], 24, 0), (0, false));
+    }
+
+    #[test]
+    fn _03_solve() {
+        let blueprints = Blueprints::parse_all(Cursor::new(EXAMPLE));
+        assert_eq!(solve(&blueprints, 24, 24, 2), (33, 9 * 12));
+        assert_eq!(solve(&blueprints, 24, 24, 1), (33, 9));
+    }
 }
\ No newline at end of file
@@ -7,7 +7,7 @@ struct Num {
     prev: usize
 }
 
-struct Mixer {
+pub struct Mixer {
     buf: Vec<i64>
 }
 
@@ -28,7 +28,32 @@ impl Mixer {
     }
 
     fn mix(&self, num_mixes: usize) -> Self {
-        // construct a linked list
+        self.mix_selected(num_mixes, |_| true)
+    }
+
+    fn mix_selected(&self, num_mixes: usize, select: impl Fn(usize) -> bool) -> Self {
+        let mut ll = self.linked_list();
+
+        for _ in 0..num_mixes {
+            Self::mix_round(&mut ll, &select);
+        }
+
+        Self { buf: self.read_linked_list(&ll) }
+    }
+
+    /// Returns the list after each of the first `rounds` rounds of mixing.
+    pub fn mix_iter(&self, rounds: usize) -> Vec<Vec<i64>> {
+        let mut ll = self.linked_list();
+
+        (0..rounds)
+            .map(|_| {
+                Self::mix_round(&mut ll, &|_| true);
+                self.read_linked_list(&ll)
+            })
+            .collect()
+    }
+
+    fn linked_list(&self) -> Vec<Num> {
         let mut ll = vec! [];
 
         for &value in &self.buf {
@@ -51,49 +76,55 @@ impl Mixer {
             }
         }
 
-        // rotate the linked list
+        ll
+    }
+
+    /// Rotate every selected number of the linked list once, in their
+    /// original order.
+    fn mix_round(ll: &mut [Num], select: &impl Fn(usize) -> bool) {
         let n = ll.len();
 
-        for _ in 0..num_mixes {
-            for i in 0..n {
-                let mut rot = (ll[i].value.abs() % ((n as i64) - 1)) * ll[i].value.signum();
-
-                // rotate right
-                while rot > 0 {
-                    let next = ll[i].next;
-                    let next_next = ll[next].next;
-                    let prev = ll[i].prev;
-
-                    ll[prev].next = next;
-                    ll[next].prev = prev;
-                    ll[next].next = i;
-                    ll[next_next].prev = i;
-                    ll[i].prev = next;
-                    ll[i].next = next_next;
-                    rot -= 1;
-                }
+        for i in (0..n).filter(|&i| select(i)) {
+            let mut rot = (ll[i].value.abs() % ((n as i64) - 1)) * ll[i].value.signum();
+
+            // rotate right
+            while rot > 0 {
+                let next = ll[i].next;
+                let next_next = ll[next].next;
+                let prev = ll[i].prev;
+
+                ll[prev].next = next;
+                ll[next].prev = prev;
+                ll[next].next = i;
+                ll[next_next].prev = i;
+                ll[i].prev = next;
+                ll[i].next = next_next;
+                rot -= 1;
+            }
 
-                // rotate left
-                while rot < 0 {
-                    let next = ll[i].next;
-                    let prev = ll[i].prev;
-                    let prev_prev = ll[prev].prev;
-
-                    ll[next].prev = prev;
-                    ll[prev].next = next;
-                    ll[prev].prev = i;
-                    ll[prev_prev].next = i;
-                    ll[i].prev = prev_prev;
-                    ll[i].next = prev;
-                    rot += 1;
-                }
+            // rotate left
+            while rot < 0 {
+                let next = ll[i].next;
+                let prev = ll[i].prev;
+                let prev_prev = ll[prev].prev;
+
+                ll[next].prev = prev;
+                ll[prev].next = next;
+                ll[prev].prev = i;
+                ll[prev_prev].next = i;
+                ll[i].prev = prev_prev;
+                ll[i].next = prev;
+                rot += 1;
             }
         }
+    }
 
-        // re-read the ordered linked list
+    /// Re-read the linked list in order, starting at the value `0`.
+    fn read_linked_list(&self, ll: &[Num]) -> Vec<i64> {
         let zero = self.buf.iter().position(|&x| x == 0).unwrap();
         let mut current = Some(zero);
-        let buf = iter::from_fn(move || {
+
+        iter::from_fn(move || {
                 if let Some(curr) = current {
                     if ll[curr].next == zero {
                         current = None;
@@ -106,24 +137,45 @@ impl Mixer {
                     None
                 }
             })
-            .collect::<Vec<_>>();
+            .collect::<Vec<_>>()
+    }
 
-        Self { buf }
+    pub fn is_permutation_of(&self, other: &Mixer) -> bool {
+        let mut ours = self.buf.clone();
+        let mut theirs = other.buf.clone();
+        ours.sort_unstable();
+        theirs.sort_unstable();
+
+        ours == theirs
     }
 
     fn at(&self, index: usize) -> i64 {
         self.buf[index % self.buf.len()]
     }
+
+    /// Returns the values 1000, 2000, and 3000 positions after the value `0`,
+    /// wrapping around the list.
+    pub fn grove_coordinates(&self) -> (i64, i64, i64) {
+        let zero = self.buf.iter().position(|&n| n == 0).unwrap();
+
+        (self.at(zero + 1000), self.at(zero + 2000), self.at(zero + 3000))
+    }
+
+    pub fn grove_sum(&self) -> i64 {
+        let (x, y, z) = self.grove_coordinates();
+
+        x + y + z
+    }
 }
 
 fn main() {
     let stdin = stdin().lock();
     let mix = Mixer::parse_all(stdin);
     let mix1 = mix.mix(1);
-    println!("{}", [1000, 2000, 3000].into_iter().map(|i| mix1.at(i)).sum::<i64>()); // 11123
+    println!("{}", mix1.grove_sum()); // 11123
 
     let mix10 = mix.scale(811589153).mix(10);
-    println!("{}", [1000, 2000, 3000].into_iter().map(|i| mix10.at(i)).sum::<i64>()); // 4248669215955
+    println!("{}", mix10.grove_sum()); // 4248669215955
 }
 
 #[cfg(test)]
@@ -146,20 +198,53 @@ mod tests {
         assert_eq!([1000, 2000, 3000].into_iter().map(|i| mix.at(i)).sum::<i64>(), 3);
     }
 
+    #[test]
+    fn _01_grove_coordinates() {
+        let mix = Mixer::parse_all(Cursor::new(EXAMPLE)).mix(1);
+        let rotated = Mixer { buf: vec! [1, 2, -3, 4, 0, 3, -2] };
+
+        assert_eq!(mix.grove_coordinates(), (4, -3, 2));
+        assert_eq!(mix.grove_sum(), 3);
+        assert_eq!(rotated.grove_coordinates(), (4, -3, 2));
+    }
+
+    #[test]
+    fn _01_mix_selected() {
+        let mix = Mixer::parse_all(Cursor::new(EXAMPLE));
+        assert_eq!(mix.mix_selected(1, |_| true).buf, mix.mix(1).buf);
+        assert_eq!(mix.mix_selected(1, |_| false).buf, vec! [0, 4, 1, 2, -3, 3, -2]);
+    }
+
+    #[test]
+    fn _01_is_permutation_of() {
+        let mix = Mixer::parse_all(Cursor::new(EXAMPLE));
+        let scaled = mix.scale(811589153);
+
+        for n in 0..=10 {
+            assert!(mix.mix(n).is_permutation_of(&mix));
+            assert!(scaled.mix(n).is_permutation_of(&scaled));
+        }
+        assert!(!scaled.is_permutation_of(&mix));
+    }
+
     #[test]
     fn _02_example() {
         let mix = Mixer::parse_all(Cursor::new(EXAMPLE)).scale(811589153);
-        assert_eq!(mix.buf,         vec! [811589153, 1623178306, -2434767459, 2434767459, -1623178306, 0, 3246356612]);
-        assert_eq!(mix.mix( 1).buf, vec! [0, -2434767459, 3246356612, -1623178306, 2434767459, 1623178306, 811589153]);
-        assert_eq!(mix.mix( 2).buf, vec! [0, 2434767459, 1623178306, 3246356612, -2434767459, -1623178306, 811589153]);
-        assert_eq!(mix.mix( 3).buf, vec! [0, 811589153, 2434767459, 3246356612, 1623178306, -1623178306, -2434767459]);
-        assert_eq!(mix.mix( 4).buf, vec! [0, 1623178306, -2434767459, 811589153, 2434767459, 3246356612, -1623178306]);
-        assert_eq!(mix.mix( 5).buf, vec! [0, 811589153, -1623178306, 1623178306, -2434767459, 3246356612, 2434767459]);
-        assert_eq!(mix.mix( 6).buf, vec! [0, 811589153, -1623178306, 3246356612, -2434767459, 1623178306, 2434767459]);
-        assert_eq!(mix.mix( 7).buf, vec! [0, -2434767459, 2434767459, 1623178306, -1623178306, 811589153, 3246356612]);
-        assert_eq!(mix.mix( 8).buf, vec! [0, 1623178306, 3246356612, 811589153, -2434767459, 2434767459, -1623178306]);
-        assert_eq!(mix.mix( 9).buf, vec! [0, 811589153, 1623178306, -2434767459, 3246356612, 2434767459, -1623178306]);
-        assert_eq!(mix.mix(10).buf, vec! [0, -2434767459, 1623178306, 3246356612, -1623178306, 2434767459, 811589153]);
+        let rounds = mix.mix_iter(10);
+
+        assert_eq!(mix.buf,   vec! [811589153, 1623178306, -2434767459, 2434767459, -1623178306, 0, 3246356612]);
+        assert_eq!(rounds.len(), 10);
+        assert_eq!(rounds[0], vec! [0, -2434767459, 3246356612, -1623178306, 2434767459, 1623178306, 811589153]);
+        assert_eq!(rounds[1], vec! [0, 2434767459, 1623178306, 3246356612, -2434767459, -1623178306, 811589153]);
+        assert_eq!(rounds[2], vec! [0, 811589153, 2434767459, 3246356612, 1623178306, -1623178306, -2434767459]);
+        assert_eq!(rounds[3], vec! [0, 1623178306, -2434767459, 811589153, 2434767459, 3246356612, -1623178306]);
+        assert_eq!(rounds[4], vec! [0, 811589153, -1623178306, 1623178306, -2434767459, 3246356612, 2434767459]);
+        assert_eq!(rounds[5], vec! [0, 811589153, -1623178306, 3246356612, -2434767459, 1623178306, 2434767459]);
+        assert_eq!(rounds[6], vec! [0, -2434767459, 2434767459, 1623178306, -1623178306, 811589153, 3246356612]);
+        assert_eq!(rounds[7], vec! [0, 1623178306, 3246356612, 811589153, -2434767459, 2434767459, -1623178306]);
+        assert_eq!(rounds[8], vec! [0, 811589153, 1623178306, -2434767459, 3246356612, 2434767459, -1623178306]);
+        assert_eq!(rounds[9], vec! [0, -2434767459, 1623178306, 3246356612, -1623178306, 2434767459, 811589153]);
+        assert_eq!(rounds[9], mix.mix(10).buf);
         assert_eq!([1000, 2000, 3000].into_iter().map(|i| mix.mix(10).at(i)).sum::<i64>(), 1623178306);
     }
 }
@@ -35,6 +35,24 @@ impl MonkeyJob {
     }
 }
 
+/// A `MonkeyJob` where every operand has been resolved to the index of an
+/// earlier node in the compiled program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompiledJob {
+    Const(i64),
+    Eq(usize, usize),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    Div(usize, usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledNode {
+    name: String,
+    job: CompiledJob,
+}
+
 #[derive(Hash, PartialEq, Eq)]
 struct Monkey {
     name: String,
@@ -78,7 +96,7 @@ impl Monkey {
     }
 }
 
-struct Monkeys {
+pub struct Monkeys {
     monkeys: HashMap<String, Monkey>
 }
 
@@ -119,37 +137,225 @@ impl Monkeys {
         }
     }
 
-    fn contains(&self, root: &str, element: &str) -> bool {
-        let monkey = &self.monkeys[root];
+    /// Flatten the monkeys into a topologically ordered program, where every
+    /// node only refers to nodes that come before it.
+    fn compile(&self) -> Vec<CompiledNode> {
+        let mut names = self.monkeys.keys().collect::<Vec<_>>();
+        let mut indices = HashMap::new();
+        let mut compiled = vec! [];
+        names.sort();
+
+        for name in names {
+            self.compile_aux(name, &mut indices, &mut compiled);
+        }
+
+        compiled
+    }
+
+    fn compile_aux(&self, name: &str, indices: &mut HashMap<String, usize>, compiled: &mut Vec<CompiledNode>) -> usize {
+        if let Some(&index) = indices.get(name) {
+            return index;
+        }
+
+        let mut operands = |lhs: &str, rhs: &str| {
+            (self.compile_aux(lhs, indices, compiled), self.compile_aux(rhs, indices, compiled))
+        };
+        let job = match self.monkeys[name].job() {
+            MonkeyJob::Const(value) => CompiledJob::Const(*value),
+            MonkeyJob::Eq(lhs, rhs) => { let (lhs, rhs) = operands(lhs, rhs); CompiledJob::Eq(lhs, rhs) },
+            MonkeyJob::Add(lhs, rhs) => { let (lhs, rhs) = operands(lhs, rhs); CompiledJob::Add(lhs, rhs) },
+            MonkeyJob::Sub(lhs, rhs) => { let (lhs, rhs) = operands(lhs, rhs); CompiledJob::Sub(lhs, rhs) },
+            MonkeyJob::Mul(lhs, rhs) => { let (lhs, rhs) = operands(lhs, rhs); CompiledJob::Mul(lhs, rhs) },
+            MonkeyJob::Div(lhs, rhs) => { let (lhs, rhs) = operands(lhs, rhs); CompiledJob::Div(lhs, rhs) },
+        };
+
+        indices.insert(name.to_string(), compiled.len());
+        compiled.push(CompiledNode { name: name.to_string(), job });
+        compiled.len() - 1
+    }
+
+    fn evaluate_compiled(compiled: &[CompiledNode], name: &str) -> i64 {
+        let mut values = Vec::with_capacity(compiled.len());
 
-        monkey.name() == element || match monkey.job() {
-            MonkeyJob::Const(_) => false,
-            _ => self.contains(&monkey.job().lhs(), element) || self.contains(&monkey.job().rhs(), element)
+        for node in compiled {
+            values.push(match node.job {
+                CompiledJob::Const(value) => value,
+                CompiledJob::Eq(lhs, rhs) => (values[lhs] == values[rhs]) as i64,
+                CompiledJob::Add(lhs, rhs) => values[lhs] + values[rhs],
+                CompiledJob::Sub(lhs, rhs) => values[lhs] - values[rhs],
+                CompiledJob::Mul(lhs, rhs) => values[lhs] * values[rhs],
+                CompiledJob::Div(lhs, rhs) => values[lhs] / values[rhs],
+            });
         }
+
+        values[compiled.iter().position(|node| node.name == name).unwrap()]
     }
 
     fn evaluate(&self, name: &str) -> i64 {
-        self.lazy_evaluate(&self.monkeys[name], &mut HashMap::new())
+        Self::evaluate_compiled(&self.compile(), name)
     }
 
-    fn backward(&self, start_at: &str, start_value: i64, name: &str) -> i64 {
-        if start_at == name {
-            start_value
-        } else {
-            match self.monkeys[start_at].job() {
-                MonkeyJob::Const(_) => panic!(),
-                MonkeyJob::Eq(lhs, rhs) if self.contains(&lhs, name) => self.backward(&lhs, self.evaluate(&rhs), name),
-                MonkeyJob::Eq(lhs, rhs) if self.contains(&rhs, name) => self.backward(&rhs,  self.evaluate(&lhs), name),
-                MonkeyJob::Add(lhs, rhs) if self.contains(&lhs, name) => self.backward(&lhs, start_value - self.evaluate(&rhs), name),
-                MonkeyJob::Add(lhs, rhs) if self.contains(&rhs, name) => self.backward(&rhs, start_value - self.evaluate(&lhs), name),
-                MonkeyJob::Sub(lhs, rhs) if self.contains(&lhs, name) => self.backward(&lhs, start_value + self.evaluate(&rhs), name),
-                MonkeyJob::Sub(lhs, rhs) if self.contains(&rhs, name) => self.backward(&rhs, self.evaluate(&lhs) - start_value, name),
-                MonkeyJob::Mul(lhs, rhs) if self.contains(&lhs, name) => self.backward(&lhs, start_value / self.evaluate(&rhs), name),
-                MonkeyJob::Mul(lhs, rhs) if self.contains(&rhs, name) => self.backward(&rhs, start_value / self.evaluate(&lhs), name),
-                MonkeyJob::Div(lhs, rhs) if self.contains(&lhs, name) => self.backward(&lhs, start_value * self.evaluate(&rhs), name),
-                MonkeyJob::Div(lhs, rhs) if self.contains(&rhs, name) => self.backward(&rhs, self.evaluate(&lhs) / start_value, name),
-                _ => panic!("could not find {} in {}", name, start_at)
+    /// Evaluates `root` as if the monkey `name` yelled `value`, without
+    /// changing any monkey.
+    pub fn evaluate_with_override(&self, name: &str, value: i64) -> i64 {
+        let mut visited = HashMap::from([(name.to_string(), value)]);
+
+        self.lazy_evaluate(&self.monkeys["root"], &mut visited)
+    }
+
+    pub fn verify_humn(&self, value: i64) -> (i64, i64) {
+        let root = self.monkeys["root"].job();
+        let mut visited = HashMap::from([("humn".to_string(), value)]);
+
+        (
+            self.lazy_evaluate(&self.monkeys[&root.lhs()], &mut visited),
+            self.lazy_evaluate(&self.monkeys[&root.rhs()], &mut visited),
+        )
+    }
+
+    /// Solves for `unknowns` given that the two operands of every monkey in
+    /// `constraints` must be equal, by evaluating every monkey as a linear
+    /// expression of the unknowns. Supports one or two unknowns, and returns
+    /// `None` if some monkey is not linear in the unknowns or there is no
+    /// unique integer solution.
+    fn solve_linear(&self, constraints: &[&str], unknowns: &[&str]) -> Option<Vec<i64>> {
+        let compiled = self.compile();
+        let n = unknowns.len();
+        let mut values: Vec<Linear> = Vec::with_capacity(compiled.len());
+
+        for node in &compiled {
+            let value = if let Some(i) = unknowns.iter().position(|&unknown| unknown == node.name) {
+                Linear::unknown(i, n)
+            } else {
+                match node.job {
+                    CompiledJob::Const(value) => Linear::constant(value as i128, n),
+                    CompiledJob::Eq(lhs, rhs) | CompiledJob::Sub(lhs, rhs) => values[lhs].sub(&values[rhs]),
+                    CompiledJob::Add(lhs, rhs) => values[lhs].add(&values[rhs]),
+                    CompiledJob::Mul(lhs, rhs) => values[lhs].mul(&values[rhs])?,
+                    CompiledJob::Div(lhs, rhs) => values[lhs].div(&values[rhs])?,
+                }
+            };
+
+            values.push(value);
+        }
+
+        // every constraint `lhs == rhs` becomes `a . x + c == 0`
+        let equations = constraints.iter()
+            .map(|&constraint| {
+                let node = compiled.iter().find(|node| node.name == constraint)?;
+                let difference = match node.job {
+                    CompiledJob::Const(_) => return None,
+                    CompiledJob::Eq(lhs, rhs) | CompiledJob::Add(lhs, rhs) | CompiledJob::Sub(lhs, rhs) | CompiledJob::Mul(lhs, rhs) | CompiledJob::Div(lhs, rhs) => {
+                        values[lhs].sub(&values[rhs])
+                    }
+                };
+
+                Some((difference.coefficients, -difference.constant))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let exact = |numerator: i128, denominator: i128| {
+            if denominator != 0 && numerator % denominator == 0 {
+                i64::try_from(numerator / denominator).ok()
+            } else {
+                None
             }
+        };
+
+        match (n, equations.as_slice()) {
+            (1, [(a, b)]) => Some(vec! [exact(*b, a[0])?]),
+            (2, [(a, b), (c, d)]) => {
+                let det = a[0] * c[1] - a[1] * c[0];
+
+                Some(vec! [
+                    exact(b * c[1] - a[1] * d, det)?,
+                    exact(a[0] * d - b * c[0], det)?,
+                ])
+            },
+            _ => None
+        }
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// `(coefficients . x + constant) / denominator` for some vector of unknowns
+/// `x`, with a positive denominator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Linear {
+    coefficients: Vec<i128>,
+    constant: i128,
+    denominator: i128,
+}
+
+impl Linear {
+    fn constant(value: i128, n: usize) -> Self {
+        Self { coefficients: vec! [0; n], constant: value, denominator: 1 }
+    }
+
+    fn unknown(i: usize, n: usize) -> Self {
+        let mut coefficients = vec! [0; n];
+        coefficients[i] = 1;
+
+        Self { coefficients, constant: 0, denominator: 1 }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.coefficients.iter().all(|&a| a == 0)
+    }
+
+    fn normalize(mut self) -> Self {
+        let g = self.coefficients.iter().fold(gcd(self.constant, self.denominator), |g, &a| gcd(g, a));
+        let g = if self.denominator < 0 { -g } else { g };
+
+        for a in self.coefficients.iter_mut() {
+            *a /= g;
+        }
+        self.constant /= g;
+        self.denominator /= g;
+        self
+    }
+
+    /// Returns `self * numerator / denominator`.
+    fn scale(&self, numerator: i128, denominator: i128) -> Self {
+        Self {
+            coefficients: self.coefficients.iter().map(|a| a * numerator).collect(),
+            constant: self.constant * numerator,
+            denominator: self.denominator * denominator,
+        }.normalize()
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self {
+            coefficients: self.coefficients.iter()
+                .zip(other.coefficients.iter())
+                .map(|(a, b)| a * other.denominator + b * self.denominator)
+                .collect(),
+            constant: self.constant * other.denominator + other.constant * self.denominator,
+            denominator: self.denominator * other.denominator,
+        }.normalize()
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.add(&other.scale(-1, 1))
+    }
+
+    fn mul(&self, other: &Self) -> Option<Self> {
+        if other.is_constant() {
+            Some(self.scale(other.constant, other.denominator))
+        } else if self.is_constant() {
+            Some(other.scale(self.constant, self.denominator))
+        } else {
+            None
+        }
+    }
+
+    fn div(&self, other: &Self) -> Option<Self> {
+        if other.is_constant() && other.constant != 0 {
+            Some(self.scale(other.denominator, other.constant))
+        } else {
+            None
         }
     }
 }
@@ -159,7 +365,7 @@ fn main() {
     let mut monkeys = Monkeys::parse_all(stdin);
 
     println!("{}", monkeys.evaluate("root")); // 276156919469632
-    println!("{}", monkeys.map("root", |job| MonkeyJob::Eq(job.lhs(), job.rhs())).backward("root", 1, "humn"));
+    println!("{}", monkeys.map("root", |job| MonkeyJob::Eq(job.lhs(), job.rhs())).solve_linear(&["root"], &["humn"]).unwrap()[0]);
 }
 
 #[cfg(test)]
@@ -189,10 +395,62 @@ hmdt: 32"#;
         assert_eq!(monkeys.evaluate("root"), 152);
     }
 
+    #[test]
+    fn _01_compile() {
+        let monkeys = Monkeys::parse_all(Cursor::new(EXAMPLE));
+        let compiled = monkeys.compile();
+
+        assert_eq!(compiled.len(), 15);
+        for (i, node) in compiled.iter().enumerate() {
+            match node.job {
+                CompiledJob::Const(_) => {},
+                CompiledJob::Eq(lhs, rhs) | CompiledJob::Add(lhs, rhs) | CompiledJob::Sub(lhs, rhs) | CompiledJob::Mul(lhs, rhs) | CompiledJob::Div(lhs, rhs) => {
+                    assert!(lhs < i && rhs < i);
+                }
+            }
+        }
+        assert_eq!(Monkeys::evaluate_compiled(&compiled, "root"), 152);
+        assert_eq!(Monkeys::evaluate_compiled(&compiled, "sjmn"), 150);
+    }
+
     #[test]
     fn _02_example() {
         let mut monkeys = Monkeys::parse_all(Cursor::new(EXAMPLE));
 
-        assert_eq!(monkeys.map("root", |job| MonkeyJob::Eq(job.lhs(), job.rhs())).backward("root", 1, "humn"), 301);
+        assert_eq!(monkeys.solve_linear(&["root"], &["humn"]), Some(vec! [301]));
+        assert_eq!(monkeys.map("root", |job| MonkeyJob::Eq(job.lhs(), job.rhs())).solve_linear(&["root"], &["humn"]), Some(vec! [301]));
+    }
+
+    #[test]
+    fn _02_two_unknowns() {
+        let example = format!("{}\nhoff: humn - eght\neght: 8\nchck: hmdt + hoff", EXAMPLE);
+        let monkeys = Monkeys::parse_all(Cursor::new(example));
+
+        assert_eq!(monkeys.solve_linear(&["root", "chck"], &["humn", "hmdt"]), Some(vec! [11, 3]));
+        assert_eq!(monkeys.solve_linear(&["root"], &["humn", "hmdt"]), None);
+        assert_eq!(monkeys.solve_linear(&["root"], &["sjmn"]), Some(vec! [2]));
+        assert_eq!(monkeys.solve_linear(&["root", "chck"], &["humn", "ljgn"]), None);
+    }
+
+    #[test]
+    fn _02_evaluate_with_override() {
+        let mut monkeys = Monkeys::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(monkeys.evaluate_with_override("humn", 5), 152);
+        assert_eq!(monkeys.evaluate_with_override("humn", 301), 300);
+        assert_eq!(monkeys.evaluate_with_override("sjmn", 10), 12);
+        assert_eq!(monkeys.evaluate("root"), 152);
+
+        let monkeys = monkeys.map("root", |job| MonkeyJob::Eq(job.lhs(), job.rhs()));
+        assert_eq!(monkeys.evaluate_with_override("humn", 301), 1);
+        assert_eq!(monkeys.evaluate_with_override("humn", 300), 0);
+    }
+
+    #[test]
+    fn _02_verify_humn() {
+        let monkeys = Monkeys::parse_all(Cursor::new(EXAMPLE));
+
+        assert_eq!(monkeys.verify_humn(301), (150, 150));
+        assert_eq!(monkeys.verify_humn(5), (2, 150));
     }
 }
\ No newline at end of file
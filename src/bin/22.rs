@@ -82,10 +82,6 @@ impl Direction {
 struct Position(i64, i64, Direction);
 
 impl Position {
-    fn starting_position() -> Self {
-        Self(0, 0, Direction::Right)
-    }
-
     fn password(&self) -> i64 {
         1000 * (1 + self.0) + 4 * (1 + self.1) + self.2.password()
     }
@@ -132,6 +128,10 @@ struct FoldedBoundsCheck {
 
 impl FoldedBoundsCheck {
     fn new(map: &Array2<char>, connected_sides: [[usize; 4]; 6]) -> Self {
+        if let Err(reason) = Self::validate_connected_sides(&connected_sides) {
+            panic!("invalid cube net -- {}", reason);
+        }
+
         let n = Self::largest_cube(map).unwrap();
         let squares = Self::split_into_squares(map, n);
         debug_assert!(map.dim().0 % n == 0);
@@ -145,6 +145,26 @@ impl FoldedBoundsCheck {
         }
     }
 
+    /// Check that every side connects to four other distinct sides, and that
+    /// every connection is mirrored by the side on the other end.
+    fn validate_connected_sides(connected_sides: &[[usize; 4]; 6]) -> Result<(), String> {
+        for (side, others) in connected_sides.iter().enumerate() {
+            for (i, &other) in others.iter().enumerate() {
+                if other >= connected_sides.len() {
+                    return Err(format!("side {} connects to unknown side {}", side, other));
+                } else if other == side {
+                    return Err(format!("side {} connects to itself", side));
+                } else if others[..i].contains(&other) {
+                    return Err(format!("side {} connects to side {} more than once", side, other));
+                } else if !connected_sides[other].contains(&side) {
+                    return Err(format!("side {} connects to side {}, but not the other way around", side, other));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn largest_cube(map: &Array2<char>) -> Option<usize> {
         let (mut y, x) = (0, 0);
 
@@ -194,6 +214,18 @@ impl FoldedBoundsCheck {
         squares
     }
 
+    /// Returns the index of the cube face that `pos` lies on, or `None` if it
+    /// is not on the cube.
+    fn face_of(&self, pos: Position) -> Option<usize> {
+        let (h, w) = self.squares.dim();
+
+        if pos.0 < 0 || pos.0 >= h as i64 || pos.1 < 0 || pos.1 >= w as i64 {
+            None
+        } else {
+            usize::try_from(self.squares[(pos.0 as usize, pos.1 as usize)]).ok()
+        }
+    }
+
     fn relative_to_abs_in(&self, in_square: usize, rel_y: usize, rel_x: usize, dir: Direction) -> Position {
         let (y, x) = (0..self.squares.dim().0)
             .find_map(|i| {
@@ -260,12 +292,9 @@ impl BoundsCheck for FoldedBoundsCheck {
 
     fn oob(&self, prev_pos: Position, pos: Position) -> Position {
         let Position(y, x, dir) = pos;
-        let (h, w) = self.squares.dim();
 
-        if y < 0 || y >= h as i64 || x < 0 || x >= w as i64 {
-            self.move_from(self.squares[(prev_pos.0 as usize, prev_pos.1 as usize)] as usize, prev_pos.0, prev_pos.1, dir)
-        } else if self.squares[(y as usize, x as usize)] < 0 {
-            self.move_from(self.squares[(prev_pos.0 as usize, prev_pos.1 as usize)] as usize, prev_pos.0, prev_pos.1, dir)
+        if self.face_of(pos).is_none() {
+            self.move_from(self.face_of(prev_pos).unwrap(), prev_pos.0, prev_pos.1, dir)
         } else {
             Position(y, x, dir)
         }
@@ -352,6 +381,15 @@ impl Map {
         }
     }
 
+    /// Returns the leftmost open tile of the first row that has one, facing
+    /// right.
+    fn starting_position(&self) -> Result<Position, String> {
+        self.map.indexed_iter()
+            .find(|(_, &ch)| ch != NAN && ch != WALL)
+            .map(|((y, x), _)| Position(y as i64, x as i64, Direction::Right))
+            .ok_or_else(|| "map has no open tile".to_string())
+    }
+
     fn fold(&self, connected_sides: [[usize; 4]; 6]) -> Self {
         Self {
             bounds_check: Box::new(FoldedBoundsCheck::new(&self.map, connected_sides)),
@@ -378,6 +416,7 @@ impl Map {
             },
             Command::Left => Position(pos.0, pos.1, pos.2.turn_left()),
             Command::Right => Position(pos.0, pos.1, pos.2.turn_right()),
+            Command::Reverse => Position(pos.0, pos.1, pos.2.opposite()),
         }
     }
 }
@@ -386,6 +425,7 @@ impl Map {
 enum Command {
     Left,
     Right,
+    Reverse,
     Move(i64)
 }
 
@@ -395,7 +435,11 @@ struct Path {
 
 impl Path {
     fn parse(reader: &mut impl BufRead) -> Self {
-        let text = reader.lines().next().unwrap().unwrap().chars().collect();
+        let text = reader.lines()
+            .next()
+            .and_then(|line| line.ok())
+            .map(|line| line.trim().chars().collect())
+            .unwrap_or_default();
 
         Self { text }
     }
@@ -411,6 +455,7 @@ impl Path {
                 match text[pos] {
                     'L' => { pos += 1; Some(Command::Left) },
                     'R' => { pos += 1; Some(Command::Right) },
+                    'U' => { pos += 1; Some(Command::Reverse) },
                     x if x.is_digit(10) => {
                         let start = pos;
                         let end = (pos..text.len())
@@ -441,9 +486,10 @@ fn main() {
         [  4, 3, 0, 1],
     ]);
     let path = Path::parse(&mut stdin);
+    let starting_position = map.starting_position().unwrap();
 
-    println!("{}", path.iter().fold(Position::starting_position(), |prev, cmd| map.take_step(prev, cmd)).password());
-    println!("{}", path.iter().fold(Position::starting_position(), |prev, cmd| folded_map.take_step(prev, cmd)).password());
+    println!("{}", path.iter().fold(starting_position, |prev, cmd| map.take_step(prev, cmd)).password());
+    println!("{}", path.iter().fold(starting_position, |prev, cmd| folded_map.take_step(prev, cmd)).password());
 }
 
 #[cfg(test)]
@@ -472,7 +518,50 @@ mod tests {
         let map = Map::parse(&mut example);
         let path = Path::parse(&mut example);
 
-        assert_eq!(path.iter().fold(Position::starting_position(), |prev, cmd| map.take_step(prev, cmd)).password(), 6032);
+        assert_eq!(path.iter().fold(map.starting_position().unwrap(), |prev, cmd| map.take_step(prev, cmd)).password(), 6032);
+    }
+
+    #[test]
+    fn _01_empty_path() {
+        for suffix in ["", "\n", "   \n"] {
+            let example = EXAMPLE.replace("10R5L5R10L4R5L5", suffix);
+            let mut example = Cursor::new(example);
+            let map = Map::parse(&mut example);
+            let path = Path::parse(&mut example);
+
+            assert_eq!(path.iter().count(), 0);
+            assert_eq!(
+                path.iter().fold(map.starting_position().unwrap(), |prev, cmd| map.take_step(prev, cmd)).password(),
+                map.starting_position().unwrap().password()
+            );
+        }
+    }
+
+    #[test]
+    fn _01_reverse() {
+        let mut example = Cursor::new(EXAMPLE.replace("10R5L5R10L4R5L5", "2U3"));
+        let map = Map::parse(&mut example);
+        let path = Path::parse(&mut example);
+        let start = map.starting_position().unwrap();
+
+        assert_eq!(path.iter().collect::<Vec<_>>(), vec! [Command::Move(2), Command::Reverse, Command::Move(3)]);
+        assert_eq!(path.iter().take(2).fold(start, |prev, cmd| map.take_step(prev, cmd)), Position(0, 10, Direction::Left));
+        assert_eq!(path.iter().fold(start, |prev, cmd| map.take_step(prev, cmd)), Position(0, 8, Direction::Left));
+        assert_eq!(map.take_step(map.take_step(start, Command::Reverse), Command::Reverse), start);
+    }
+
+    #[test]
+    fn _01_starting_position() {
+        let map = Map::parse(&mut Cursor::new(EXAMPLE));
+        let blocked = Map::parse(&mut Cursor::new("  #..\n  ...\n"));
+        let empty_rows = Map::parse(&mut Cursor::new("    \n ##.\n"));
+        let walls = Map::parse(&mut Cursor::new(" ##\n#\n"));
+
+        assert_eq!(map.starting_position(), Ok(Position(0, 8, Direction::Right)));
+        assert_eq!(map.starting_position().unwrap().password(), 1036);
+        assert_eq!(blocked.starting_position(), Ok(Position(0, 3, Direction::Right)));
+        assert_eq!(empty_rows.starting_position(), Ok(Position(1, 3, Direction::Right)));
+        assert!(walls.starting_position().is_err());
     }
 
     #[test]
@@ -489,6 +578,71 @@ mod tests {
         ]);
         let path = Path::parse(&mut example);
 
-        assert_eq!(path.iter().fold(Position::starting_position(), |prev, cmd| map.take_step(prev, cmd)).password(), 5031);
+        assert_eq!(path.iter().fold(map.starting_position().unwrap(), |prev, cmd| map.take_step(prev, cmd)).password(), 5031);
+    }
+
+    #[test]
+    fn _02_face_of() {
+        let map = Map::parse(&mut Cursor::new(EXAMPLE));
+        let bounds_check = FoldedBoundsCheck::new(&map.map, [
+            // R, D, L, U
+            [  5, 1, 2, 3],
+            [  2, 0, 5, 4],
+            [  3, 0, 1, 4],
+            [  5, 0, 2, 4],
+            [  5, 3, 2, 1],
+            [  0, 3, 4, 1],
+        ]);
+
+        assert_eq!(bounds_check.face_of(map.starting_position().unwrap()), Some(0));
+        assert_eq!(bounds_check.face_of(Position(4, 0, Direction::Right)), Some(1));
+        assert_eq!(bounds_check.face_of(Position(7, 11, Direction::Right)), Some(3));
+        assert_eq!(bounds_check.face_of(Position(11, 15, Direction::Right)), Some(5));
+        assert_eq!(bounds_check.face_of(Position(0, 0, Direction::Right)), None);
+        assert_eq!(bounds_check.face_of(Position(-1, 8, Direction::Up)), None);
+    }
+
+    #[test]
+    fn _02_validate_connected_sides() {
+        assert_eq!(FoldedBoundsCheck::validate_connected_sides(&[
+            [  5, 1, 2, 3],
+            [  2, 0, 5, 4],
+            [  3, 0, 1, 4],
+            [  5, 0, 2, 4],
+            [  5, 3, 2, 1],
+            [  0, 3, 4, 1],
+        ]), Ok(()));
+        assert_eq!(FoldedBoundsCheck::validate_connected_sides(&[
+            [  1, 5, 3, 2],
+            [  4, 5, 0, 2],
+            [  1, 0, 3, 4],
+            [  4, 2, 0, 5],
+            [  1, 2, 3, 5],
+            [  4, 3, 0, 1],
+        ]), Ok(()));
+        assert!(FoldedBoundsCheck::validate_connected_sides(&[
+            [  5, 1, 2, 3],
+            [  2, 0, 5, 4],
+            [  3, 0, 1, 4],
+            [  5, 0, 2, 4],
+            [  5, 3, 2, 1],
+            [  0, 3, 4, 2],
+        ]).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid cube net")]
+    fn _02_malformed_cube_net() {
+        let mut example = Cursor::new(EXAMPLE);
+
+        Map::parse(&mut example).fold([
+            // R, D, L, U
+            [  5, 1, 2, 3],
+            [  2, 0, 5, 4],
+            [  3, 0, 1, 4],
+            [  5, 0, 2, 4],
+            [  5, 3, 2, 1],
+            [  0, 3, 4, 0],
+        ]);
     }
-}
\ No newline at end of file
+}
@@ -62,6 +62,21 @@ impl Elf {
         ];
     }
 
+    fn propose(&self, busy: &HashSet<(i64, i64)>) -> (i64, i64) {
+        if self.adjacents().any(|(y, x)| busy.contains(&(y, x))) {
+            let valid_direction = self.candidates.iter()
+                .find(|direction| direction.is_valid().all(|(dy, dx)| !busy.contains(&(self.y+dy, self.x+dx))));
+
+            if let Some(direction) = valid_direction {
+                (self.y + direction.delta().0, self.x + direction.delta().1)
+            } else {
+                (self.y, self.x)
+            }
+        } else {
+            (self.y, self.x)
+        }
+    }
+
     fn adjacents(&self) -> impl Iterator<Item=(i64, i64)> {
         [
             (self.y-1, self.x+1),
@@ -76,6 +91,30 @@ impl Elf {
     }
 }
 
+/// Returns the position every elf proposes to move to, in the same order as
+/// `elves`.
+#[cfg(not(feature = "parallel"))]
+fn proposals(elves: &[Elf], busy: &HashSet<(i64, i64)>) -> Vec<(i64, i64)> {
+    elves.iter().map(|elf| elf.propose(busy)).collect()
+}
+
+/// Returns the position every elf proposes to move to, in the same order as
+/// `elves`. The elves are split into one contiguous chunk per thread, and the
+/// chunks are joined back in order so collisions resolve deterministically.
+#[cfg(feature = "parallel")]
+fn proposals(elves: &[Elf], busy: &HashSet<(i64, i64)>) -> Vec<(i64, i64)> {
+    let num_threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = elves.len().div_ceil(num_threads).max(1);
+
+    std::thread::scope(|scope| {
+        let handles = elves.chunks(chunk_size)
+            .map(|part| scope.spawn(move || part.iter().map(|elf| elf.propose(busy)).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
 struct Grove {
     elves: Vec<Elf>,
 }
@@ -110,7 +149,7 @@ impl Debug for Grove {
 
 impl Grove {
     fn parse(reader: impl BufRead) -> Self {
-        let elves = reader.lines()
+        let coords = reader.lines()
             .enumerate()
             .flat_map(|(i, line)| {
                 line.unwrap()
@@ -118,7 +157,7 @@ impl Grove {
                     .enumerate()
                     .filter_map(|(j, ch)| {
                         if ch == ELF {
-                            Some(Elf::new(i as i64, j as i64))
+                            Some((i as i64, j as i64))
                         } else {
                             None
                         }
@@ -126,13 +165,50 @@ impl Grove {
                     .collect::<Vec<_>>()
                     .into_iter()
             })
+            .collect::<Vec<_>>();
+
+        Self::from_coords(&coords)
+    }
+
+    /// Place an elf at each of the given `(y, x)` coordinates.
+    fn from_coords(coords: &[(i64, i64)]) -> Self {
+        let elves = coords.iter()
+            .map(|&(y, x)| Elf::new(y, x))
             .collect();
 
         Self { elves }
     }
 
     fn rounds(&self, n: usize) -> (Self, usize) {
+        let (grove, stable_at, _) = self.rounds_aux(n);
+
+        (grove, stable_at.unwrap_or(n))
+    }
+
+    fn run_until_stable(&self, max: usize) -> Option<(Self, usize)> {
+        match self.rounds_aux(max) {
+            (grove, Some(stable_at), _) => Some((grove, stable_at)),
+            (_, None, _) => None
+        }
+    }
+
+    /// Same as `rounds`, but also returns how many elves changed position in
+    /// each round that was played.
+    #[cfg(test)]
+    fn rounds_with_stats(&self, n: usize) -> (Self, Vec<usize>) {
+        let (grove, _, num_moved) = self.rounds_aux(n);
+
+        (grove, num_moved)
+    }
+
+    #[cfg(test)]
+    fn render_round(&self, k: usize) -> String {
+        format!("{:?}", self.rounds(k).0)
+    }
+
+    fn rounds_aux(&self, n: usize) -> (Self, Option<usize>, Vec<usize>) {
         let mut elves = self.elves.clone();
+        let mut num_moved = vec! [];
 
         for round_num in 0..n {
             let mut to_move = vec! [];
@@ -141,20 +217,7 @@ impl Grove {
                 .collect::<HashSet<_>>();
             let mut occurances = HashMap::new();
 
-            for elf in &elves {
-                let (ny, nx) = if elf.adjacents().any(|(y, x)| busy.contains(&(y, x))) {
-                    let valid_direction = elf.candidates.iter()
-                        .find(|direction| direction.is_valid().all(|(dy, dx)| !busy.contains(&(elf.y+dy, elf.x+dx))));
-
-                    if let Some(direction) = valid_direction {
-                        (elf.y + direction.delta().0, elf.x + direction.delta().1)
-                    } else {
-                        (elf.y, elf.x)
-                    }
-                } else {
-                    (elf.y, elf.x)
-                };
-
+            for (elf, (ny, nx)) in elves.iter().zip(proposals(&elves, &busy)) {
                 let mut new_elf = elf.clone();
                 new_elf.rotate_candidates();
 
@@ -162,24 +225,28 @@ impl Grove {
                 occurances.entry((ny, nx)).and_modify(|v| *v += 1).or_insert(1);
             }
 
-            let mut moved = false;
+            let mut moved = 0;
             elves = to_move.into_iter()
                 .map(|(elf, new_pos)| {
                     if occurances[&new_pos] > 1 {
                         elf
                     } else {
-                        moved = moved || elf.y != new_pos.0 || elf.x != new_pos.1;
+                        if elf.y != new_pos.0 || elf.x != new_pos.1 {
+                            moved += 1;
+                        }
+
                         elf.move_to(new_pos.0, new_pos.1)
                     }
                 })
                 .collect::<Vec<_>>();
 
-            if !moved {
-                return (Self { elves }, round_num + 1);
+            num_moved.push(moved);
+            if moved == 0 {
+                return (Self { elves }, Some(round_num + 1), num_moved);
             }
         }
 
-        (Self { elves }, n)
+        (Self { elves }, None, num_moved)
     }
 
     fn area(&self) -> usize {
@@ -210,7 +277,7 @@ fn main() {
     let grove = Grove::parse(stdin);
 
     println!("{}", grove.rounds(10).0.num_empty());
-    println!("{}", grove.rounds(100_000).1);
+    println!("{}", grove.run_until_stable(100_000).unwrap().1);
 }
 
 #[cfg(test)]
@@ -244,6 +311,22 @@ mod tests {
 ..#..");
     }
 
+    #[test]
+    fn _01_from_coords() {
+        let grove = Grove::from_coords(&[(1, 2), (1, 3), (2, 2), (4, 2), (4, 3)]);
+
+        assert_eq!(
+            format!("{:?}", grove.rounds(10).0),
+            format!("{:?}", Grove::parse(Cursor::new(SMALL_EXAMPLE)).rounds(10).0)
+        );
+        assert_eq!(format!("{:?}", grove.rounds(10).0).trim(), "..#..
+....#
+#....
+....#
+.....
+..#..");
+    }
+
     #[test]
     fn _01_example() {
         let grove = Grove::parse(Cursor::new(EXAMPLE));
@@ -262,6 +345,33 @@ mod tests {
         assert_eq!(grove.rounds(10).0.num_empty(), 110);
     }
 
+    #[test]
+    fn _01_render_round() {
+        let grove = Grove::parse(Cursor::new(EXAMPLE));
+
+        assert_eq!(grove.render_round(3).trim(), "......#....
+....#....#.
+.#..#...#..
+......#...#
+..#..#.#...
+#..#.....#.
+......##...
+.##.#....#.
+..#........
+......#....");
+        assert_eq!(grove.render_round(5).trim(), "......#....
+...........
+.#..#.....#
+........#..
+.....##...#
+#.#.####...
+..........#
+...##..#...
+.#.........
+.........#.
+...#..#....");
+    }
+
     #[test]
     fn _02_example() {
         let (grove, n) = Grove::parse(Cursor::new(EXAMPLE)).rounds(1000);
@@ -280,4 +390,25 @@ mod tests {
 .......#......");
         assert_eq!(n, 20);
     }
+
+    #[test]
+    fn _02_run_until_stable() {
+        let grove = Grove::parse(Cursor::new(EXAMPLE));
+
+        assert_eq!(grove.run_until_stable(1000).map(|(_, n)| n), Some(20));
+        assert_eq!(grove.run_until_stable(20).map(|(_, n)| n), Some(20));
+        assert!(grove.run_until_stable(19).is_none());
+    }
+
+    #[test]
+    fn _02_rounds_with_stats() {
+        let grove = Grove::parse(Cursor::new(EXAMPLE));
+        let (stable, num_moved) = grove.rounds_with_stats(1000);
+
+        assert_eq!(format!("{:?}", stable), format!("{:?}", grove.rounds(1000).0));
+        assert_eq!(num_moved.len(), 20);
+        assert_eq!(num_moved[19], 0);
+        assert_eq!(num_moved[16..], [2, 2, 2, 0]);
+        assert!(num_moved[..19].iter().all(|&moved| moved > 0));
+    }
 }
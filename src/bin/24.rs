@@ -1,4 +1,4 @@
-use std::{io::{prelude::*, stdin}, collections::{HashSet, VecDeque}, fmt::Display};
+use std::{io::{prelude::*, stdin}, collections::{HashSet, BinaryHeap}, cmp::Reverse, fmt::Display};
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum Direction {
@@ -51,6 +51,17 @@ impl Direction {
             Self::West => (0, -1),
         }
     }
+
+    /// Returns the position one step in this direction, or `None` if that
+    /// would leave the top or left edge of the grid.
+    fn step(self, position: (usize, usize)) -> Option<(usize, usize)> {
+        let (dy, dx) = self.delta();
+
+        Some((
+            position.0.checked_add_signed(dy as isize)?,
+            position.1.checked_add_signed(dx as isize)?,
+        ))
+    }
 }
 
 struct Blizzard {
@@ -158,12 +169,41 @@ impl Valley {
         )
     }
 
+    /// Returns every position occupied by at least one blizzard at `time`.
+    #[cfg(test)]
+    fn blizzards_at(&self, time: usize) -> HashSet<(usize, usize)> {
+        (0..self.dims.0)
+            .flat_map(|y| (0..self.dims.1).map(move |x| (y, x)))
+            .filter(|&position| self.has_blizzard_at(position, time))
+            .collect()
+    }
+
+    /// Returns if some blizzard is at `position` at `time`, only blizzards in
+    /// the same row or column can ever reach it.
+    fn has_blizzard_at(&self, position: (usize, usize), time: usize) -> bool {
+        self.rows[position.0].iter()
+            .chain(self.cols[position.1].iter())
+            .any(|blizzard| blizzard.position_at(time) == position)
+    }
+
+    /// Returns the number of blizzards, which stays the same at every `time`.
+    #[cfg(test)]
+    fn blizzard_count(&self) -> usize {
+        self.rows.iter().map(|row| row.len()).sum()
+    }
+
     #[cfg(test)]
     fn display_at(&self, time: usize) -> String {
+        let occupied = self.blizzards_at(time);
         let mut f = String::new();
 
         for row in 0..self.dims.0 {
             for col in 0..self.dims.1 {
+                if !occupied.contains(&(row, col)) {
+                    f += if self.walls.contains(&(row, col)) { "#" } else { "." };
+                    continue;
+                }
+
                 let blizzards = self.rows[row].iter()
                     .filter(|blizzard| blizzard.position_at(time) == (row, col))
                     .chain(
@@ -177,9 +217,7 @@ impl Valley {
                 directions.sort_unstable();
                 directions.dedup();
 
-                if directions.is_empty() {
-                    f += if self.walls.contains(&(row, col)) { "#" } else { "." };
-                } else if directions.len() == 1 {
+                if directions.len() == 1 {
                     f += &format!("{}", directions[0]);
                 } else {
                     f += &format!("{}", directions.len());
@@ -192,12 +230,21 @@ impl Valley {
         f.trim().to_string()
     }
 
+    fn trip_legs(&self) -> (usize, usize, usize) {
+        let start_at = self.start_point();
+        let end_at = self.end_point();
+
+        let t1 = shortest_path(self, start_at, 0, end_at);
+        let t2 = shortest_path(self, end_at, t1, start_at);
+        let t3 = shortest_path(self, start_at, t2, end_at);
+
+        (t1, t2 - t1, t3 - t2)
+    }
+
     fn is_empty_at(&self, position: (usize, usize), time: usize) -> bool {
         position.0 < self.dims.0 && position.1 < self.dims.1 &&
-            (!self.walls.contains(&position) &&
-            self.rows[position.0].iter()
-                .chain(self.cols[position.1].iter())
-                .all(|blizzard| blizzard.position_at(time) != position))
+            !self.walls.contains(&position) &&
+            !self.has_blizzard_at(position, time)
     }
 }
 
@@ -207,48 +254,42 @@ fn manhattan_distance(start_at: (usize, usize), end_at: (usize, usize)) -> usize
 }
 
 fn shortest_path(valley: &Valley, start_at: (usize, usize), start_time: usize, end_at: (usize, usize)) -> usize {
-    let mut so_far = usize::MAX;
+    shortest_path_aux(valley, start_at, start_time, end_at).0
+}
+
+/// Search the valley ordered by `t + manhattan_distance`, which never
+/// overestimates the remaining time, so the first time we reach `end_at` is
+/// optimal. Returns the arrival time and the number of visited states.
+fn shortest_path_aux(valley: &Valley, start_at: (usize, usize), start_time: usize, end_at: (usize, usize)) -> (usize, usize) {
     let mut visited = HashSet::new();
-    let mut to_visit = VecDeque::new();
-    to_visit.push_back((start_at, start_time));
+    let mut to_visit = BinaryHeap::new();
+    to_visit.push(Reverse((manhattan_distance(start_at, end_at) + start_time, start_time, start_at)));
 
-    while let Some((position, t)) = to_visit.pop_front() {
+    while let Some(Reverse((_, t, position))) = to_visit.pop() {
         if position == end_at {
-            so_far = so_far.min(t);
-            continue; // best so far?
-        } else if manhattan_distance(position, end_at) + t > so_far {
-            continue; // worse than best so far
+            return (t, visited.len());
         } else if !valley.is_empty_at(position, t) {
             continue; // hit by blizzard
         }
 
-        for next_direction in Direction::all() {
-            let (dy, dx) = next_direction.delta();
-            let next_position = (
-                (position.0 as i64 + dy) as usize,
-                (position.1 as i64 + dx) as usize,
-            );
+        let next_positions = Direction::all()
+            .filter_map(|next_direction| next_direction.step(position))
+            .chain([position]);
 
+        for next_position in next_positions {
             if valley.is_empty_at(next_position, t + 1) && visited.insert((next_position, t + 1)) {
-                to_visit.push_back((next_position, t + 1));
+                to_visit.push(Reverse((manhattan_distance(next_position, end_at) + t + 1, t + 1, next_position)));
             }
         }
-
-        if valley.is_empty_at(position, t + 1) && visited.insert((position, t + 1)) {
-            to_visit.push_back((position, t + 1));
-        }
     }
 
-    so_far
+    (usize::MAX, visited.len())
 }
 
 fn shortest_path3(valley: &Valley) -> usize {
-    let start_at = valley.start_point();
-    let end_at = valley.end_point();
+    let (t1, t2, t3) = valley.trip_legs();
 
-    let t = shortest_path(valley, start_at, 0, end_at);
-    let t = shortest_path(valley, end_at, t, start_at);
-    shortest_path(valley, start_at, t, end_at)
+    t1 + t2 + t3
 }
 
 fn main() {
@@ -299,6 +340,40 @@ mod tests {
 #####.#");
     }
 
+    #[test]
+    fn _01_blizzards_at() {
+        let small_valley = Valley::parse(Cursor::new(SMALL_EXAMPLE));
+        let valley = Valley::parse(Cursor::new(EXAMPLE));
+
+        assert_eq!(small_valley.blizzards_at(0), HashSet::from([(2, 1), (4, 4)]));
+        assert_eq!(small_valley.blizzards_at(2), HashSet::from([(2, 3), (1, 4)]));
+
+        for time in 0..12 {
+            let occupied = valley.blizzards_at(time);
+
+            for row in 0..valley.dims.0 {
+                for col in 0..valley.dims.1 {
+                    let is_open = !valley.walls.contains(&(row, col)) && !occupied.contains(&(row, col));
+
+                    assert_eq!(valley.is_empty_at((row, col), time), is_open);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn _01_blizzard_count() {
+        let valley = Valley::parse(Cursor::new(EXAMPLE));
+        let occupied = (0..12).map(|time| valley.blizzards_at(time).len()).collect::<Vec<_>>();
+
+        assert_eq!(Valley::parse(Cursor::new(SMALL_EXAMPLE)).blizzard_count(), 2);
+        assert_eq!(valley.blizzard_count(), 19);
+        assert_eq!(valley.cols.iter().map(|col| col.len()).sum::<usize>(), 19);
+        assert_eq!(occupied[0], 19);
+        assert!(occupied.iter().all(|&n| n <= valley.blizzard_count()));
+        assert!(occupied.iter().any(|&n| n < valley.blizzard_count()));
+    }
+
     #[test]
     fn _01_example() {
         let valley = Valley::parse(Cursor::new(EXAMPLE));
@@ -308,9 +383,35 @@ mod tests {
         assert_eq!(shortest_path(&valley, valley.start_point(), 0, valley.end_point()), 18);
     }
 
+    #[test]
+    fn _01_step_off_grid() {
+        let valley = Valley::parse(Cursor::new(EXAMPLE));
+        let start_at = valley.start_point();
+
+        assert_eq!(Direction::North.step(start_at), None);
+        assert_eq!(Direction::West.step((1, 0)), None);
+        assert_eq!(Direction::South.step(start_at), Some((1, 1)));
+        assert_eq!(shortest_path(&valley, valley.end_point(), 18, start_at), 41);
+    }
+
+    #[test]
+    fn _01_visited_states() {
+        let valley = Valley::parse(Cursor::new(EXAMPLE));
+        let (t, num_visited) = shortest_path_aux(&valley, valley.start_point(), 0, valley.end_point());
+
+        assert_eq!(t, 18);
+        assert!(num_visited < 79, "{} states visited, the breadth-first search visited 79", num_visited);
+    }
+
     #[test]
     fn _02_example() {
         let valley = Valley::parse(Cursor::new(EXAMPLE));
         assert_eq!(shortest_path3(&valley), 54);
     }
+
+    #[test]
+    fn _02_trip_legs() {
+        let valley = Valley::parse(Cursor::new(EXAMPLE));
+        assert_eq!(valley.trip_legs(), (18, 23, 13));
+    }
 }
\ No newline at end of file
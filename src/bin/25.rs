@@ -1,13 +1,21 @@
 use std::{io::{prelude::*, stdin}, str::FromStr, fmt::Display, iter::Sum};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Snafu(i64);
+pub struct Snafu(i64);
 
 impl Snafu {
     #[cfg(test)]
     fn new(n: i64) -> Self {
         Self(n)
     }
+
+    pub fn checked_sum<I: Iterator<Item=Snafu>>(mut iter: I) -> Option<Snafu> {
+        iter.try_fold(Snafu(0), |acc, snafu| acc.0.checked_add(snafu.0).map(Snafu))
+    }
+
+    fn sum_strs<'a>(lines: impl Iterator<Item=&'a str>) -> Result<Snafu, ()> {
+        lines.map(Snafu::try_from).sum()
+    }
 }
 
 impl Sum<Snafu> for Snafu {
@@ -16,60 +24,84 @@ impl Sum<Snafu> for Snafu {
     }
 }
 
-impl FromStr for Snafu {
-    type Err = ();
+impl TryFrom<&str> for Snafu {
+    type Error = ();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.chars().fold(0, |n, ch| {
-            5 * n + match ch {
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.chars().try_fold(0, |n, ch| {
+            let digit = match ch {
                 '2' => 2,
                 '1' => 1,
                 '0' => 0,
                 '-' => -1,
                 '=' => -2,
-                _ => panic!(),
-            }
-        })))
+                _ => return Err(()),
+            };
+
+            Ok(5 * n + digit)
+        }).map(Self)
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+/// Returns the balanced base-5 digits of `n`, least significant first.
+fn balanced_digits(mut n: i64) -> Vec<i64> {
+    let mut digits = vec! [];
+
+    while n != 0 {
+        let to_add = match n % 5 {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 => -2,
+            4 => -1,
+            _ => unreachable!(),
+        };
+
+        n = (n - to_add) / 5;
+        digits.push(to_add);
     }
+
+    digits
+}
+
+/// Returns the number of digits in the SNAFU representation of `n`.
+pub fn snafu_len(n: i64) -> usize {
+    balanced_digits(n).len()
 }
 
 impl Display for Snafu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut s = vec! [];
-        let mut n = self.0;
-
-        while n != 0 {
-            let to_add = match n % 5 {
-                0 => 0,
-                1 => 1,
-                2 => 2,
-                3 => -2,
-                4 => -1,
-                _ => unreachable!(),
-            };
-
-            n = (n - to_add) / 5;
-            s.push(match to_add {
+        let s = balanced_digits(self.0).into_iter()
+            .rev()
+            .map(|digit| match digit {
                 2  => '2',
                 1  => '1',
                 0  => '0',
                 -1 => '-',
                 -2 => '=',
                 _  => unreachable!(),
-            });
-        }
+            })
+            .collect::<String>();
 
-        write!(f, "{}", s.into_iter().rev().collect::<String>())
+        write!(f, "{}", s)
     }
 }
 
 fn main() {
     let stdin = stdin().lock();
-    let numbers = stdin.lines()
-        .filter_map(|line| line.ok().and_then(|line| line.parse::<Snafu>().ok()))
+    let lines = stdin.lines()
+        .map(|line| line.unwrap())
         .collect::<Vec<_>>();
 
-    println!("{}", numbers.iter().cloned().sum::<Snafu>());
+    println!("{}", Snafu::sum_strs(lines.iter().map(|line| line.trim())).expect("invalid snafu number"));
 }
 
 #[cfg(test)]
@@ -93,4 +125,34 @@ mod tests {
         assert_eq!(Snafu::from_str("122"), Ok(Snafu(37)));
         assert_eq!(format!("{}", Snafu::new(4890)), "2=-1=0");
     }
+
+    #[test]
+    fn _01_checked_sum() {
+        const EXAMPLE: [&str; 13] = [
+            "1=-0-2", "12111", "2=0=", "21", "2=01", "111", "20012", "112", "1=-1=", "1-12", "12", "1=", "122"
+        ];
+        let numbers = EXAMPLE.iter().map(|s| s.parse::<Snafu>().unwrap());
+
+        assert_eq!(format!("{}", Snafu::checked_sum(numbers).unwrap()), "2=-1=0");
+        assert_eq!(Snafu::checked_sum([Snafu::new(i64::MAX), Snafu::new(1)].into_iter()), None);
+    }
+
+    #[test]
+    fn _01_sum_strs() {
+        const EXAMPLE: &str = "1=-0-2\n12111\n2=0=\n21\n2=01\n111\n20012\n112\n1=-1=\n1-12\n12\n1=\n122";
+
+        assert_eq!(Snafu::try_from("2=-1=0"), Ok(Snafu::new(4890)));
+        assert_eq!(Snafu::try_from("2=x"), Err(()));
+        assert_eq!(Snafu::sum_strs(EXAMPLE.lines()).map(|snafu| snafu.to_string()), Ok("2=-1=0".to_string()));
+        assert_eq!(Snafu::sum_strs(["1", "3"].into_iter()), Err(()));
+    }
+
+    #[test]
+    fn _01_snafu_len() {
+        assert_eq!(snafu_len(4890), 6);
+
+        for n in 0..5000 {
+            assert_eq!(snafu_len(n), format!("{}", Snafu::new(n)).len());
+        }
+    }
 }